@@ -0,0 +1,684 @@
+use crate::db::{self, TicketEvent};
+use crate::parser::Ticket;
+use crate::workflow::{ColumnCategory, Workflow};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Range used when the caller does not pass a start date.
+const DEFAULT_RANGE_WEEKS: i64 = 12;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Format of `ticket_events.created_at` (SQLite `datetime('now')`, UTC).
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartPoint {
+    /// Date (`YYYY-MM-DD`) or ticket ID, depending on the series
+    pub x: String,
+    pub y: f64,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartSeries {
+    pub name: String,
+    pub points: Vec<ChartPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketTiming {
+    pub ticket_id: String,
+    pub epic_id: String,
+    pub status: String,
+    pub created_at: Option<String>,
+    /// First time the ticket entered an "active" column
+    pub started_at: Option<String>,
+    /// When the ticket last entered a "done" column, if it is still done
+    pub completed_at: Option<String>,
+    pub lead_time_days: Option<f64>,
+    pub cycle_time_days: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p85: f64,
+    pub p95: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpicFlow {
+    pub epic_id: String,
+    /// Tickets completed within the range
+    pub completed: usize,
+    pub lead_time: Option<Percentiles>,
+    pub cycle_time: Option<Percentiles>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowAnalytics {
+    pub from: String,
+    pub to: String,
+    /// Days from creation to done, one point per ticket completed in range
+    pub lead_time: ChartSeries,
+    /// Days from first start to done, one point per ticket completed in range
+    pub cycle_time: ChartSeries,
+    /// Tickets completed per week, keyed by the Monday starting the week
+    pub throughput: ChartSeries,
+    /// Days each ticket currently in an "active" column has been there
+    pub in_progress_age: ChartSeries,
+    pub average_in_progress_age_days: Option<f64>,
+    pub epics: Vec<EpicFlow>,
+}
+
+/// Inclusive range of calendar days (UTC).
+#[derive(Debug, Clone, Copy)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    /// Parse `YYYY-MM-DD` bounds. `to` defaults to `today` and `from` to
+    /// twelve weeks before `to`.
+    pub fn parse(from: Option<&str>, to: Option<&str>, today: NaiveDate) -> Result<Self, String> {
+        let parse = |s: &str| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", s))
+        };
+        let to = to.map(parse).transpose()?.unwrap_or(today);
+        let from = from
+            .map(parse)
+            .transpose()?
+            .unwrap_or(to - Duration::weeks(DEFAULT_RANGE_WEEKS));
+
+        if from > to {
+            return Err(format!("Start date {} is after end date {}", from, to));
+        }
+        Ok(Self { from, to })
+    }
+
+    pub fn contains(&self, at: NaiveDateTime) -> bool {
+        (self.from..=self.to).contains(&at.date())
+    }
+}
+
+pub fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT).ok()
+}
+
+fn format_timestamp(at: NaiveDateTime) -> String {
+    at.format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn days_between(start: NaiveDateTime, end: NaiveDateTime) -> f64 {
+    (end - start).num_seconds().max(0) as f64 / SECONDS_PER_DAY
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// A stretch of time; `None` as the end means it is still ongoing.
+pub type Period = (NaiveDateTime, Option<NaiveDateTime>);
+
+/// Period covering all of time, for state that predates the history.
+const ALWAYS: Period = (NaiveDateTime::MIN, None);
+
+/// Key moments of one ticket, rebuilt from its recorded events.
+#[derive(Debug, Default, Clone)]
+pub struct Timeline {
+    pub created: Option<NaiveDateTime>,
+    pub started: Option<NaiveDateTime>,
+    pub completed: Option<NaiveDateTime>,
+    /// Start of the current stretch in an "active" column
+    pub active_since: Option<NaiveDateTime>,
+    /// Every stretch spent in a "done" column, reopenings included
+    pub done_periods: Vec<Period>,
+}
+
+fn is_status_event(event: &TicketEvent) -> bool {
+    matches!(event.event_type.as_str(), "created" | "status_changed")
+}
+
+impl Timeline {
+    /// `events` must belong to `ticket` and be in recorded order. Tickets
+    /// that predate the history start at their first recorded event.
+    pub fn build(ticket: &Ticket, events: &[&TicketEvent], workflow: &Workflow) -> Self {
+        let mut timeline = Timeline::default();
+
+        for event in events.iter().filter(|e| is_status_event(e)) {
+            let Some(at) = parse_timestamp(&event.created_at) else {
+                continue;
+            };
+            timeline.created.get_or_insert(at);
+
+            let to = workflow.category(event.to_value.as_deref().unwrap_or_default());
+            let from = workflow.category(event.from_value.as_deref().unwrap_or_default());
+
+            if to == Some(ColumnCategory::Active) {
+                timeline.started.get_or_insert(at);
+                if from != Some(ColumnCategory::Active) {
+                    timeline.active_since = Some(at);
+                }
+            }
+
+            let open = timeline.done_periods.last_mut().filter(|(_, end)| end.is_none());
+            match (to == Some(ColumnCategory::Done), open) {
+                (true, None) => timeline.done_periods.push((at, None)),
+                (false, Some(period)) => period.1 = Some(at),
+                _ => {}
+            }
+            // Reopening a done ticket clears its completion
+            timeline.completed = (to == Some(ColumnCategory::Done)).then_some(at);
+        }
+
+        if !workflow.is_done(&ticket.status) {
+            timeline.completed = None;
+        }
+        if workflow.category(&ticket.status) != Some(ColumnCategory::Active) {
+            timeline.active_since = None;
+        }
+        timeline
+    }
+
+    pub fn lead_time_days(&self) -> Option<f64> {
+        Some(days_between(self.created?, self.completed?))
+    }
+
+    pub fn cycle_time_days(&self) -> Option<f64> {
+        Some(days_between(self.started?, self.completed?))
+    }
+}
+
+fn events_by_ticket(events: &[TicketEvent]) -> HashMap<&str, Vec<&TicketEvent>> {
+    let mut by_ticket: HashMap<&str, Vec<&TicketEvent>> = HashMap::new();
+    for event in events {
+        by_ticket.entry(event.ticket_id.as_str()).or_default().push(event);
+    }
+    by_ticket
+}
+
+/// Timeline of every ticket in the snapshot.
+pub fn timelines<'a>(
+    tickets: &'a [Ticket],
+    events: &[TicketEvent],
+    workflow: &Workflow,
+) -> Vec<(&'a Ticket, Timeline)> {
+    let by_ticket = events_by_ticket(events);
+    tickets
+        .iter()
+        .map(|ticket| {
+            let events = by_ticket.get(ticket.id.as_str()).map(Vec::as_slice).unwrap_or_default();
+            (ticket, Timeline::build(ticket, events, workflow))
+        })
+        .collect()
+}
+
+/// Linear interpolation between closest ranks.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+fn percentiles(values: &[f64]) -> Option<Percentiles> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Some(Percentiles {
+        p50: percentile(&sorted, 0.50),
+        p85: percentile(&sorted, 0.85),
+        p95: percentile(&sorted, 0.95),
+    })
+}
+
+pub fn ticket_timings(
+    tickets: &[Ticket],
+    events: &[TicketEvent],
+    workflow: &Workflow,
+    range: DateRange,
+) -> Vec<TicketTiming> {
+    let mut timings: Vec<TicketTiming> = timelines(tickets, events, workflow)
+        .into_iter()
+        .filter(|(_, t)| t.completed.is_none_or(|at| range.contains(at)))
+        .map(|(ticket, t)| TicketTiming {
+            ticket_id: ticket.id.clone(),
+            epic_id: ticket.epic.clone(),
+            status: ticket.status.clone(),
+            created_at: t.created.map(format_timestamp),
+            started_at: t.started.map(format_timestamp),
+            completed_at: t.completed.map(format_timestamp),
+            lead_time_days: t.lead_time_days(),
+            cycle_time_days: t.cycle_time_days(),
+        })
+        .collect();
+    timings.sort_by(|a, b| a.ticket_id.cmp(&b.ticket_id));
+    timings
+}
+
+pub fn compute(
+    tickets: &[Ticket],
+    events: &[TicketEvent],
+    workflow: &Workflow,
+    range: DateRange,
+    now: NaiveDateTime,
+) -> FlowAnalytics {
+    let timelines = timelines(tickets, events, workflow);
+
+    let mut completed: Vec<(&Ticket, &Timeline, NaiveDateTime)> = timelines
+        .iter()
+        .filter_map(|(ticket, t)| t.completed.filter(|at| range.contains(*at)).map(|at| (*ticket, t, at)))
+        .collect();
+    completed.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.id.cmp(&b.0.id)));
+
+    let point = |ticket: &Ticket, at: NaiveDateTime, days: f64| ChartPoint {
+        x: at.date().to_string(),
+        y: days,
+        label: Some(ticket.id.clone()),
+    };
+    let lead_time = completed
+        .iter()
+        .filter_map(|(ticket, t, at)| t.lead_time_days().map(|d| point(ticket, *at, d)))
+        .collect();
+    let cycle_time = completed
+        .iter()
+        .filter_map(|(ticket, t, at)| t.cycle_time_days().map(|d| point(ticket, *at, d)))
+        .collect();
+
+    let mut weeks: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut week = week_start(range.from);
+    while week <= range.to {
+        weeks.insert(week, 0);
+        week += Duration::weeks(1);
+    }
+    for (_, _, at) in &completed {
+        *weeks.entry(week_start(at.date())).or_default() += 1;
+    }
+    let throughput = weeks
+        .into_iter()
+        .map(|(week, count)| ChartPoint {
+            x: week.to_string(),
+            y: count as f64,
+            label: None,
+        })
+        .collect();
+
+    let mut ages: Vec<ChartPoint> = timelines
+        .iter()
+        .filter_map(|(ticket, t)| {
+            t.active_since.map(|since| ChartPoint {
+                x: ticket.id.clone(),
+                y: days_between(since, now),
+                label: Some(ticket.title.clone()),
+            })
+        })
+        .collect();
+    ages.sort_by(|a, b| b.y.total_cmp(&a.y));
+    let average_in_progress_age_days =
+        (!ages.is_empty()).then(|| ages.iter().map(|p| p.y).sum::<f64>() / ages.len() as f64);
+
+    let mut by_epic: BTreeMap<&str, (Vec<f64>, Vec<f64>, usize)> = BTreeMap::new();
+    for (ticket, t, _) in &completed {
+        if ticket.epic.is_empty() {
+            continue;
+        }
+        let entry = by_epic.entry(ticket.epic.as_str()).or_default();
+        entry.0.extend(t.lead_time_days());
+        entry.1.extend(t.cycle_time_days());
+        entry.2 += 1;
+    }
+    let epics = by_epic
+        .into_iter()
+        .map(|(epic_id, (lead, cycle, count))| EpicFlow {
+            epic_id: epic_id.to_string(),
+            completed: count,
+            lead_time: percentiles(&lead),
+            cycle_time: percentiles(&cycle),
+        })
+        .collect();
+
+    let series = |name: &str, points| ChartSeries {
+        name: name.to_string(),
+        points,
+    };
+    FlowAnalytics {
+        from: range.from.to_string(),
+        to: range.to.to_string(),
+        lead_time: series("Lead time (days)", lead_time),
+        cycle_time: series("Cycle time (days)", cycle_time),
+        throughput: series("Completed per week", throughput),
+        in_progress_age: series("Days in progress", ages),
+        average_in_progress_age_days,
+        epics,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeChange {
+    pub date: String,
+    pub ticket_id: String,
+    /// 1 when the ticket joined the scope, -1 when it left
+    pub delta: i64,
+    pub points: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnChart {
+    /// Epic or sprint the series describe
+    pub scope_id: String,
+    pub from: String,
+    pub to: String,
+    /// Tickets in scope at the end of each day; steps mark scope changes
+    pub scope: ChartSeries,
+    /// Tickets done at the end of each day (burnup)
+    pub completed: ChartSeries,
+    /// Tickets still open at the end of each day (burndown)
+    pub remaining: ChartSeries,
+    /// The same three series weighted by estimate, unestimated tickets as 1
+    pub scope_points: ChartSeries,
+    pub completed_points: ChartSeries,
+    pub remaining_points: ChartSeries,
+    pub scope_changes: Vec<ScopeChange>,
+    /// Tickets completed per day over the last four weeks
+    pub recent_throughput: f64,
+    /// Day the open tickets would be done at the recent throughput
+    pub projected_completion: Option<String>,
+}
+
+/// Days of recent history the projected completion is based on.
+const PROJECTION_WINDOW_DAYS: i64 = 28;
+
+/// One ticket's part in a burn chart.
+pub struct BurnItem<'a> {
+    pub ticket_id: &'a str,
+    pub points: f64,
+    /// When the ticket belonged to the epic or sprint
+    pub scope: Vec<Period>,
+    pub done: &'a [Period],
+}
+
+fn covers(periods: &[Period], at: NaiveDateTime) -> bool {
+    periods.iter().any(|(start, end)| *start <= at && end.is_none_or(|end| end > at))
+}
+
+/// Daily burndown and burnup over `range`, counted at the end of each day.
+pub fn burn_chart(scope_id: &str, items: &[BurnItem], range: DateRange, now: NaiveDateTime) -> BurnChart {
+    let mut days: Vec<(String, [f64; 4])> = Vec::new();
+    let mut day = range.from;
+    while day <= range.to {
+        let at = day.and_hms_opt(23, 59, 59).expect("valid time").min(now);
+        let mut totals = [0.0; 4];
+        for item in items.iter().filter(|item| covers(&item.scope, at)) {
+            totals[0] += 1.0;
+            totals[1] += item.points;
+            if covers(item.done, at) {
+                totals[2] += 1.0;
+                totals[3] += item.points;
+            }
+        }
+        days.push((day.to_string(), totals));
+        day += Duration::days(1);
+    }
+
+    let series = |name: &str, value: fn(&[f64; 4]) -> f64| ChartSeries {
+        name: name.to_string(),
+        points: days
+            .iter()
+            .map(|(x, totals)| ChartPoint {
+                x: x.clone(),
+                y: value(totals),
+                label: None,
+            })
+            .collect(),
+    };
+
+    let mut scope_changes = Vec::new();
+    for item in items {
+        for (start, end) in &item.scope {
+            let changes = [(Some(*start), 1), (*end, -1)];
+            for (at, delta) in changes {
+                // Tickets that predate the history have no known start
+                if let Some(at) = at.filter(|at| *at != NaiveDateTime::MIN && range.contains(*at)) {
+                    scope_changes.push(ScopeChange {
+                        date: at.date().to_string(),
+                        ticket_id: item.ticket_id.to_string(),
+                        delta,
+                        points: item.points,
+                    });
+                }
+            }
+        }
+    }
+    scope_changes.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.ticket_id.cmp(&b.ticket_id)));
+
+    let window_start = now - Duration::days(PROJECTION_WINDOW_DAYS);
+    let current: Vec<&BurnItem> = items.iter().filter(|item| covers(&item.scope, now)).collect();
+    let recently_done = current
+        .iter()
+        .filter(|item| covers(item.done, now))
+        .filter(|item| item.done.last().is_some_and(|(start, _)| *start >= window_start))
+        .count();
+    let open = current.iter().filter(|item| !covers(item.done, now)).count();
+    let recent_throughput = recently_done as f64 / PROJECTION_WINDOW_DAYS as f64;
+    let projected_completion = if open == 0 {
+        Some(now.date().to_string())
+    } else if recent_throughput > 0.0 {
+        let days = (open as f64 / recent_throughput).ceil() as i64;
+        Some((now.date() + Duration::days(days)).to_string())
+    } else {
+        None
+    };
+
+    BurnChart {
+        scope_id: scope_id.to_string(),
+        from: range.from.to_string(),
+        to: range.to.to_string(),
+        scope: series("Scope", |t| t[0]),
+        completed: series("Completed", |t| t[2]),
+        remaining: series("Remaining", |t| t[0] - t[2]),
+        scope_points: series("Scope (points)", |t| t[1]),
+        completed_points: series("Completed (points)", |t| t[3]),
+        remaining_points: series("Remaining (points)", |t| t[1] - t[3]),
+        scope_changes,
+        recent_throughput,
+        projected_completion,
+    }
+}
+
+/// When a ticket's `field` ("epic" or "sprint") was `value`. The value at
+/// creation is worked out backwards from `current` and the recorded changes.
+fn membership_periods(
+    field: &str,
+    current: &str,
+    events: &[&TicketEvent],
+    created: Option<NaiveDateTime>,
+    value: &str,
+) -> Vec<Period> {
+    let event_type = format!("{}_changed", field);
+    let changes: Vec<(NaiveDateTime, &TicketEvent)> = events
+        .iter()
+        .filter(|e| e.event_type == event_type)
+        .filter_map(|e| parse_timestamp(&e.created_at).map(|at| (at, *e)))
+        .collect();
+
+    let mut member_of = changes
+        .first()
+        .map_or(current, |(_, e)| e.from_value.as_deref().unwrap_or_default());
+    let mut since = created.unwrap_or(NaiveDateTime::MIN);
+    let mut periods = Vec::new();
+
+    for (at, event) in changes {
+        if member_of == value {
+            periods.push((since, Some(at)));
+        }
+        member_of = event.to_value.as_deref().unwrap_or_default();
+        since = at;
+    }
+    if member_of == value {
+        periods.push((since, None));
+    }
+    periods
+}
+
+/// Burn items for the tickets that were ever part of `value`.
+fn burn_items<'a>(
+    timelines: &'a [(&'a Ticket, Timeline)],
+    by_ticket: &HashMap<&str, Vec<&TicketEvent>>,
+    workflow: &Workflow,
+    field: &str,
+    value: &str,
+) -> Vec<BurnItem<'a>> {
+    timelines
+        .iter()
+        .filter_map(|(ticket, timeline)| {
+            let events = by_ticket.get(ticket.id.as_str()).map(Vec::as_slice).unwrap_or_default();
+            let current = match field {
+                "sprint" => ticket.sprint.as_deref().unwrap_or_default(),
+                _ => ticket.epic.as_str(),
+            };
+            let scope = membership_periods(field, current, events, timeline.created, value);
+            if scope.is_empty() {
+                return None;
+            }
+
+            // Done tickets without history count as done all along
+            let done = if timeline.done_periods.is_empty() && workflow.is_done(&ticket.status) {
+                std::slice::from_ref(&ALWAYS)
+            } else {
+                timeline.done_periods.as_slice()
+            };
+            Some(BurnItem {
+                ticket_id: &ticket.id,
+                points: ticket.estimate.unwrap_or(1.0).max(0.0),
+                scope,
+                done,
+            })
+        })
+        .collect()
+}
+
+pub fn epic_burn_chart(
+    tickets: &[Ticket],
+    events: &[TicketEvent],
+    workflow: &Workflow,
+    epic_id: &str,
+    range: DateRange,
+    now: NaiveDateTime,
+) -> BurnChart {
+    let by_ticket = events_by_ticket(events);
+    let timelines = timelines(tickets, events, workflow);
+    let items = burn_items(&timelines, &by_ticket, workflow, "epic", epic_id);
+    burn_chart(epic_id, &items, range, now)
+}
+
+pub fn sprint_burn_chart(
+    tickets: &[Ticket],
+    events: &[TicketEvent],
+    workflow: &Workflow,
+    sprint_id: &str,
+    range: DateRange,
+    now: NaiveDateTime,
+) -> BurnChart {
+    let by_ticket = events_by_ticket(events);
+    let timelines = timelines(tickets, events, workflow);
+    let items = burn_items(&timelines, &by_ticket, workflow, "sprint", sprint_id);
+    burn_chart(sprint_id, &items, range, now)
+}
+
+/// Snapshot tickets, their status events and the workflow of a project.
+fn load(project_path: &str) -> Result<(Vec<Ticket>, Vec<TicketEvent>, Workflow), String> {
+    let tickets = db::get_all_tickets_snapshot(project_path)?;
+    let events = db::get_project_events(project_path)?;
+    let workflow = Workflow::load_or_default(&crate::m2k_dir(project_path));
+    Ok((tickets, events, workflow))
+}
+
+pub fn flow_analytics(project_path: &str, from: Option<&str>, to: Option<&str>) -> Result<FlowAnalytics, String> {
+    let now = Utc::now().naive_utc();
+    let range = DateRange::parse(from, to, now.date())?;
+    let (tickets, events, workflow) = load(project_path)?;
+    Ok(compute(&tickets, &events, &workflow, range, now))
+}
+
+pub fn project_ticket_timings(
+    project_path: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<TicketTiming>, String> {
+    let range = DateRange::parse(from, to, Utc::now().date_naive())?;
+    let (tickets, events, workflow) = load(project_path)?;
+    Ok(ticket_timings(&tickets, &events, &workflow, range))
+}
+
+/// Burndown and burnup of one epic. Without a start date the series begin
+/// when the first of its tickets was created.
+pub fn project_epic_burn_chart(
+    project_path: &str,
+    epic_id: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<BurnChart, String> {
+    let now = Utc::now().naive_utc();
+    let mut range = DateRange::parse(from, to, now.date())?;
+    let (tickets, events, workflow) = load(project_path)?;
+
+    if !db::get_all_epics_snapshot(project_path)?.iter().any(|e| e.id == epic_id) {
+        return Err(format!("Epic {} not found", epic_id));
+    }
+
+    if from.is_none() {
+        let first = timelines(&tickets, &events, &workflow)
+            .iter()
+            .filter(|(ticket, _)| ticket.epic == epic_id)
+            .filter_map(|(_, t)| t.created)
+            .min();
+        if let Some(first) = first.filter(|first| first.date() <= range.to) {
+            range.from = first.date();
+        }
+    }
+
+    Ok(epic_burn_chart(&tickets, &events, &workflow, epic_id, range, now))
+}
+
+/// Burndown and burnup of one sprint, over its dates unless a range is
+/// given.
+pub fn project_sprint_burn_chart(
+    project_path: &str,
+    sprint_id: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<BurnChart, String> {
+    let now = Utc::now().naive_utc();
+    let sprint = db::get_sprints_snapshot(project_path)?
+        .into_iter()
+        .find(|s| s.id == sprint_id)
+        .ok_or(format!("Sprint {} not found", sprint_id))?;
+
+    // Open sprints are charted up to today, and sprints that have not begun
+    // up to their first day
+    let today = now.date().to_string();
+    let mut end = sprint.end_date.clone().filter(|end| *end < today).unwrap_or(today);
+    if let Some(start) = sprint.start_date.as_ref().filter(|start| **start > end) {
+        end = start.clone();
+    }
+    let range = DateRange::parse(
+        from.or(sprint.start_date.as_deref()),
+        Some(to.unwrap_or(&end)),
+        now.date(),
+    )?;
+    let (tickets, events, workflow) = load(project_path)?;
+
+    Ok(sprint_burn_chart(&tickets, &events, &workflow, sprint_id, range, now))
+}
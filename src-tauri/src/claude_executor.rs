@@ -1,9 +1,16 @@
+use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
 use std::fs;
+use std::io::Write;
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command as TokioCommand;
+use tokio::process::{Child, Command as TokioCommand};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use regex::Regex;
 use tauri::{AppHandle, Emitter};
 use crate::claude_logger::{ClaudeLogger, LogLevel};
 use crate::claude_session::ClaudeSession;
@@ -14,6 +21,97 @@ pub struct TaskRequest {
     pub prompt: String,
     pub workspace_path: Option<String>,
     pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// Kind of failure, as far as the error text tells.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    RateLimit,
+    Network,
+    /// Rejected credentials, e.g. an expired token that may refresh
+    Auth,
+    /// No login at all; retrying cannot help until the user signs in
+    NotLoggedIn,
+    Timeout,
+    Other,
+}
+
+lazy_static::lazy_static! {
+    /// HTTP status codes that tell a failure apart, only as whole numbers so
+    /// durations and IDs do not match
+    static ref STATUS_CODES: Regex = Regex::new(r"\b(401|429|529)\b").unwrap();
+}
+
+impl ErrorClass {
+    pub fn classify(error: &str) -> Self {
+        let error = error.to_lowercase();
+        let has = |needles: &[&str]| needles.iter().any(|n| error.contains(n));
+        let codes: Vec<&str> = STATUS_CODES.find_iter(&error).map(|m| m.as_str()).collect();
+        let has_code = |code: &str| codes.contains(&code);
+
+        if has(&["rate limit", "rate_limit", "too many requests", "overloaded"]) || has_code("429") || has_code("529") {
+            ErrorClass::RateLimit
+        } else if has(&["not authenticated", "not logged in", "auth login"]) {
+            ErrorClass::NotLoggedIn
+        } else if has(&["unauthorized", "token expired", "authentication"]) || has_code("401") {
+            ErrorClass::Auth
+        } else if has(&["network", "connection", "econnreset", "econnrefused", "etimedout", "dns", "socket"]) {
+            ErrorClass::Network
+        } else if error.starts_with("task timeout") {
+            ErrorClass::Timeout
+        } else {
+            ErrorClass::Other
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorClass::RateLimit => "rate_limit",
+            ErrorClass::Network => "network",
+            ErrorClass::Auth => "auth",
+            ErrorClass::NotLoggedIn => "not_logged_in",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::Other => "other",
+        }
+    }
+}
+
+/// When a failed task runs again. The wait doubles with every attempt, from
+/// `initial_backoff_secs` up to `max_backoff_secs`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included
+    pub max_attempts: u32,
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    pub retry_on: Vec<ErrorClass>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 300,
+            retry_on: vec![ErrorClass::RateLimit, ErrorClass::Network, ErrorClass::Auth],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before the attempt after `attempt`, or `None` when the failure
+    /// is final.
+    pub fn next_delay(&self, attempt: u32, class: ErrorClass) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.retry_on.contains(&class) {
+            return None;
+        }
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let secs = self.initial_backoff_secs.saturating_mul(factor).min(self.max_backoff_secs);
+        Some(Duration::from_secs(secs))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,6 +124,121 @@ pub struct TaskResult {
     pub duration_ms: Option<u64>,
 }
 
+/// Most stdout kept in memory and stored as the task result
+const OUTPUT_CAP_BYTES: usize = 256 * 1024;
+/// Lines of stderr attached to the error of a failed task
+const STDERR_TAIL_LINES: usize = 20;
+/// How long to wait for the output readers once the process is gone
+const READER_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time between SIGTERM and SIGKILL when stopping a task
+const TERMINATE_GRACE: Duration = Duration::from_secs(5);
+
+pub const CANCELLED_ERROR: &str = "Task cancelled by user";
+
+/// How the wait for the `claude` process ended.
+enum Exit {
+    Finished(std::io::Result<std::process::ExitStatus>),
+    TimedOut,
+    Cancelled,
+}
+
+/// Collected stdout. Up to `cap` bytes stay in memory; past that the whole
+/// output goes to `spill_path` and the result keeps only the beginning.
+struct OutputCapture {
+    cap: usize,
+    text: String,
+    spill_path: PathBuf,
+    spill: Option<fs::File>,
+    truncated: bool,
+}
+
+impl OutputCapture {
+    fn new(cap: usize, spill_path: PathBuf) -> Self {
+        Self {
+            cap,
+            text: String::new(),
+            spill_path,
+            spill: None,
+            truncated: false,
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        if let Some(file) = &mut self.spill {
+            writeln!(file, "{}", line).ok();
+            return;
+        }
+        if !self.truncated && self.text.len() + line.len() < self.cap {
+            self.text.push_str(line);
+            self.text.push('\n');
+            return;
+        }
+        if self.truncated {
+            return;
+        }
+
+        self.truncated = true;
+        match fs::File::create(&self.spill_path) {
+            Ok(mut file) => {
+                file.write_all(self.text.as_bytes()).ok();
+                writeln!(file, "{}", line).ok();
+                self.spill = Some(file);
+            }
+            Err(e) => log::warn!("Failed to create {}: {}", self.spill_path.display(), e),
+        }
+
+        let mut end = (self.cap - self.text.len()).min(line.len());
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.text.push_str(&line[..end]);
+    }
+
+    /// Take what has been captured so far. The spill file, if any, is closed.
+    fn finish(&mut self) -> String {
+        let text = std::mem::take(&mut self.text);
+        match (self.truncated, self.spill.take().is_some()) {
+            (false, _) => text,
+            (true, true) => format!(
+                "{}\n[output truncated at {} bytes; full output in {}]",
+                text,
+                self.cap,
+                self.spill_path.display()
+            ),
+            (true, false) => format!("{}\n[output truncated at {} bytes]", text, self.cap),
+        }
+    }
+}
+
+/// Stop the process and everything it started: SIGTERM to its process group,
+/// then SIGKILL once `grace` has passed. Leftover group members are killed
+/// even when the main process exits in time, since they would keep the
+/// output pipes open.
+async fn terminate(child: &mut Child, grace: Duration) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let group = -(pid as libc::pid_t);
+        // SAFETY: kill only sends a signal; the group is the one the child leads
+        unsafe { libc::kill(group, libc::SIGTERM) };
+        let exited = tokio::time::timeout(grace, child.wait()).await.is_ok();
+        unsafe { libc::kill(group, libc::SIGKILL) };
+        if !exited {
+            child.wait().await.ok();
+        }
+        return;
+    }
+
+    child.kill().await.ok();
+}
+
+/// `message` followed by the last lines the process wrote to stderr.
+fn with_stderr_tail(message: String, tail: &[String]) -> String {
+    if tail.is_empty() {
+        return message;
+    }
+    format!("{}\n{}", message, tail.join("\n"))
+}
+
 #[derive(Clone)]
 pub struct ClaudeExecutor {
     workspace_base: PathBuf,
@@ -43,11 +256,30 @@ impl ClaudeExecutor {
         })
     }
 
+    /// Head the log segment of an attempt.
+    pub fn log_attempt_start(&self, task_id: &str, attempt: u32, max_attempts: u32) {
+        self.logger.log(
+            task_id,
+            LogLevel::Info,
+            &format!("Attempt {} of {}", attempt, max_attempts),
+            None,
+        ).ok();
+    }
+
+    /// Offset in the task log where the next entry will start
+    pub fn log_size(&self, task_id: &str) -> u64 {
+        self.logger.log_size(task_id)
+    }
+
+    /// Run a task. Setting `cancel` to true stops the `claude` process; the
+    /// task then fails with `CANCELLED_ERROR`. The workspace is removed
+    /// however the task ends.
     pub async fn execute_task(
         &self,
         task_id: &str,
         request: TaskRequest,
         app: AppHandle,
+        mut cancel: watch::Receiver<bool>,
     ) -> Result<TaskResult, String> {
         // Check authentication before execution
         let session = ClaudeSession::check_auth_status()?;
@@ -76,6 +308,35 @@ impl ClaudeExecutor {
             None,
         )?;
 
+        let result = self.run_in_workspace(task_id, &request, &workspace, app, &mut cancel).await;
+
+        if let Err(e) = self.cleanup_workspace(&workspace) {
+            self.logger.log(task_id, LogLevel::Warning, &e, None).ok();
+        }
+
+        let (output, files_modified) = result?;
+        Ok(TaskResult {
+            task_id: task_id.to_string(),
+            status: TaskStatus::Completed,
+            output: Some(output),
+            files_modified,
+            error: None,
+            duration_ms: Some(start.elapsed().as_millis() as u64),
+        })
+    }
+
+    /// Copy the project in, run `claude` and list the files it left. Returns
+    /// the captured output and the files.
+    async fn run_in_workspace(
+        &self,
+        task_id: &str,
+        request: &TaskRequest,
+        workspace: &Path,
+        app: AppHandle,
+        cancel: &mut watch::Receiver<bool>,
+    ) -> Result<(String, Vec<String>), String> {
+        let start = std::time::Instant::now();
+
         // Copy files if needed
         if let Some(project_path) = &request.workspace_path {
             self.logger.log(
@@ -84,19 +345,20 @@ impl ClaudeExecutor {
                 "Copying workspace files",
                 None,
             )?;
-            self.copy_workspace_files(project_path, &workspace)?;
+            self.copy_workspace_files(project_path, workspace)?;
         }
 
         // Execute with logging
         let output = self.execute_claude_code_with_logging(
-            &workspace,
+            workspace,
             &request.prompt,
             request.timeout_secs.unwrap_or(300),
-            app.clone(),
+            app,
             task_id,
+            cancel,
         ).await?;
 
-        let files_modified = self.scan_modified_files(&workspace)?;
+        let files_modified = self.scan_modified_files(workspace)?;
 
         self.logger.log(
             task_id,
@@ -107,16 +369,7 @@ impl ClaudeExecutor {
             })),
         )?;
 
-        self.cleanup_workspace(&workspace)?;
-
-        Ok(TaskResult {
-            task_id: task_id.to_string(),
-            status: TaskStatus::Completed,
-            output: Some(output),
-            files_modified,
-            error: None,
-            duration_ms: Some(start.elapsed().as_millis() as u64),
-        })
+        Ok((output, files_modified))
     }
 
     fn create_workspace(&self, task_id: &str) -> Result<PathBuf, String> {
@@ -141,7 +394,7 @@ impl ClaudeExecutor {
             if entry.path().is_dir() {
                 self.copy_dir_recursive(&entry.path(), &dest_path)?;
             } else {
-                fs::copy(&entry.path(), &dest_path)
+                fs::copy(entry.path(), &dest_path)
                     .map_err(|e| format!("Failed to copy file: {}", e))?;
             }
         }
@@ -162,7 +415,7 @@ impl ClaudeExecutor {
             if entry.path().is_dir() {
                 self.copy_dir_recursive(&entry.path(), &dest_path)?;
             } else {
-                fs::copy(&entry.path(), &dest_path)
+                fs::copy(entry.path(), &dest_path)
                     .map_err(|e| format!("Failed to copy file: {}", e))?;
             }
         }
@@ -176,7 +429,12 @@ impl ClaudeExecutor {
         timeout_secs: u64,
         app: AppHandle,
         task_id: &str,
+        cancel: &mut watch::Receiver<bool>,
     ) -> Result<String, String> {
+        if *cancel.borrow() {
+            return Err(CANCELLED_ERROR.to_string());
+        }
+
         let mut cmd = TokioCommand::new("claude");
         cmd.arg("--workspace")
             .arg(workspace)
@@ -184,6 +442,9 @@ impl ClaudeExecutor {
             .arg(prompt)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
+        // Own process group, so stopping the task reaches whatever claude starts
+        #[cfg(unix)]
+        cmd.process_group(0);
 
         let mut child = cmd.spawn()
             .map_err(|e| {
@@ -199,60 +460,112 @@ impl ClaudeExecutor {
 
         let mut reader = BufReader::new(stdout).lines();
         let mut err_reader = BufReader::new(stderr).lines();
-        let mut output = String::new();
 
         // Stream stdout with logging
         let logger_clone = self.logger.clone();
         let task_id_str = task_id.to_string();
         let app_clone = app.clone();
+        // Shared with the reader, so a reader that has to be aborted still
+        // leaves behind what it read
+        let capture = Arc::new(Mutex::new(OutputCapture::new(OUTPUT_CAP_BYTES, self.logger.output_path(task_id))));
+        let capture_clone = capture.clone();
 
-        tokio::spawn(async move {
+        let stdout_task = tokio::spawn(async move {
             while let Ok(Some(line)) = reader.next_line().await {
                 logger_clone.log(&task_id_str, LogLevel::Info, &line, None).ok();
                 app_clone.emit("task-output", serde_json::json!({
                     "task_id": task_id_str,
                     "output": line
                 })).ok();
+                capture_clone.lock().unwrap().push_line(&line);
             }
         });
 
-        // Stream stderr with logging
+        // Stream stderr with logging, keeping the tail for errors
         let logger_clone = self.logger.clone();
         let task_id_str = task_id.to_string();
 
-        tokio::spawn(async move {
+        let stderr_tail = Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_TAIL_LINES)));
+        let tail_clone = stderr_tail.clone();
+
+        let stderr_task = tokio::spawn(async move {
             while let Ok(Some(line)) = err_reader.next_line().await {
                 logger_clone.log(&task_id_str, LogLevel::Warning, &line, None).ok();
+                let mut tail = tail_clone.lock().unwrap();
+                if tail.len() == STDERR_TAIL_LINES {
+                    tail.pop_front();
+                }
+                tail.push_back(line);
             }
         });
 
-        // Wait with timeout
-        let result = tokio::time::timeout(
-            std::time::Duration::from_secs(timeout_secs),
-            child.wait()
-        ).await;
+        // Wait with timeout, unless cancelled first
+        let exit = tokio::select! {
+            result = tokio::time::timeout(Duration::from_secs(timeout_secs), child.wait()) => match result {
+                Ok(result) => Exit::Finished(result),
+                Err(_) => Exit::TimedOut,
+            },
+            Ok(_) = cancel.wait_for(|cancelled| *cancelled) => Exit::Cancelled,
+        };
+        if !matches!(exit, Exit::Finished(_)) {
+            terminate(&mut child, TERMINATE_GRACE).await;
+        }
+
+        self.drain_readers(task_id, stdout_task, stderr_task).await;
+        let output = capture.lock().unwrap().finish();
+        let stderr_tail = Vec::from(std::mem::take(&mut *stderr_tail.lock().unwrap()));
 
-        match result {
-            Ok(Ok(status)) => {
+        match exit {
+            Exit::Finished(Ok(status)) => {
                 if status.success() {
                     Ok(output)
                 } else {
                     let err = format!("Claude exited with status: {}", status);
                     self.logger.log(task_id, LogLevel::Error, &err, None).ok();
-                    Err(err)
+                    Err(with_stderr_tail(err, &stderr_tail))
                 }
             }
-            Ok(Err(e)) => {
+            Exit::Finished(Err(e)) => {
                 let err = format!("Failed to wait: {}", e);
                 self.logger.log(task_id, LogLevel::Error, &err, None).ok();
-                Err(err)
+                Err(with_stderr_tail(err, &stderr_tail))
             }
-            Err(_) => {
-                child.kill().await.ok();
+            Exit::TimedOut => {
                 let err = "Task timeout".to_string();
                 self.logger.log(task_id, LogLevel::Error, &err, None).ok();
-                Err(err)
+                Err(with_stderr_tail(err, &stderr_tail))
             }
+            Exit::Cancelled => {
+                self.logger.log(task_id, LogLevel::Warning, CANCELLED_ERROR, None).ok();
+                Err(CANCELLED_ERROR.to_string())
+            }
+        }
+    }
+
+    /// Wait for the stdout and stderr readers, which finish once the process
+    /// has closed its pipes. Something the CLI started may keep them open, so
+    /// the wait is bounded; whatever the readers captured before then is kept.
+    async fn drain_readers(
+        &self,
+        task_id: &str,
+        stdout_task: JoinHandle<()>,
+        stderr_task: JoinHandle<()>,
+    ) {
+        let stdout_abort = stdout_task.abort_handle();
+        let stderr_abort = stderr_task.abort_handle();
+
+        if tokio::time::timeout(READER_DRAIN_TIMEOUT, async { tokio::join!(stdout_task, stderr_task) })
+            .await
+            .is_err()
+        {
+            stdout_abort.abort();
+            stderr_abort.abort();
+            self.logger.log(
+                task_id,
+                LogLevel::Warning,
+                "Output still open after the process ended; output may be incomplete",
+                None,
+            ).ok();
         }
     }
 
@@ -278,3 +591,56 @@ impl ClaudeExecutor {
             .map_err(|e| format!("Failed to cleanup workspace: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_by_error_text() {
+        let cases = [
+            ("API Error: 429 Too Many Requests", ErrorClass::RateLimit),
+            ("Overloaded (529)", ErrorClass::RateLimit),
+            ("Claude Code not authenticated. Run 'claude auth login' first.", ErrorClass::NotLoggedIn),
+            ("OAuth token expired", ErrorClass::Auth),
+            ("request failed with status 401", ErrorClass::Auth),
+            ("Connection reset by peer (ECONNRESET)", ErrorClass::Network),
+            ("Task timeout after 600s", ErrorClass::Timeout),
+            // Codes only count as whole numbers
+            ("Exited after 4290ms in session 1401", ErrorClass::Other),
+            ("Process exited with code 1", ErrorClass::Other),
+        ];
+        for (error, class) in cases {
+            assert_eq!(ErrorClass::classify(error), class, "{}", error);
+        }
+    }
+
+    #[test]
+    fn default_policy_does_not_retry_missing_login() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.next_delay(1, ErrorClass::NotLoggedIn), None);
+        assert_eq!(policy.next_delay(1, ErrorClass::Other), None);
+        assert!(policy.next_delay(1, ErrorClass::Auth).is_some());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_and_stops_at_max_attempts() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff_secs: 10,
+            max_backoff_secs: 30,
+            retry_on: vec![ErrorClass::Network],
+        };
+        let delays: Vec<_> = (1..=5).map(|attempt| policy.next_delay(attempt, ErrorClass::Network)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Some(Duration::from_secs(10)),
+                Some(Duration::from_secs(20)),
+                Some(Duration::from_secs(30)),
+                Some(Duration::from_secs(30)),
+                None,
+            ]
+        );
+    }
+}
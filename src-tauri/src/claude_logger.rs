@@ -35,6 +35,7 @@ impl ClaudeLogger {
     }
 
     /// Create task-specific log file
+    #[allow(dead_code)]
     pub fn create_task_log(&self, task_id: &str) -> Result<PathBuf, String> {
         let log_path = self.log_dir.join(format!("{}.log", task_id));
         File::create(&log_path)
@@ -91,11 +92,33 @@ impl ClaudeLogger {
             .map_err(|e| format!("Failed to read log: {}", e))
     }
 
+    /// Current length of the task log, where the next entry will start
+    pub fn log_size(&self, task_id: &str) -> u64 {
+        fs::metadata(self.log_dir.join(format!("{}.log", task_id)))
+            .map(|m| m.len())
+            .unwrap_or(0)
+    }
+
+    /// Read the part of a task log between two offsets, e.g. one attempt
+    pub fn read_log_segment(&self, task_id: &str, start: u64, end: Option<u64>) -> Result<String, String> {
+        let log = self.read_task_log(task_id)?;
+        let bytes = log.as_bytes();
+        let end = end.map_or(bytes.len(), |end| (end as usize).min(bytes.len()));
+        let start = (start as usize).min(end);
+        Ok(String::from_utf8_lossy(&bytes[start..end]).to_string())
+    }
+
     /// Stream log file (tail -f style)
+    #[allow(dead_code)]
     pub fn get_log_path(&self, task_id: &str) -> PathBuf {
         self.log_dir.join(format!("{}.log", task_id))
     }
 
+    /// File holding the full stdout of a task whose output outgrew the cap
+    pub fn output_path(&self, task_id: &str) -> PathBuf {
+        self.log_dir.join(format!("{}.output.txt", task_id))
+    }
+
     /// Cleanup old logs (older than N days)
     pub fn cleanup_old_logs(&self, days: u64) -> Result<usize, String> {
         let cutoff = Utc::now() - chrono::Duration::days(days as i64);
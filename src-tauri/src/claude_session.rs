@@ -6,23 +6,13 @@ lazy_static::lazy_static! {
     static ref SESSION_STATE: Mutex<SessionState> = Mutex::new(SessionState::default());
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SessionState {
     pub authenticated: bool,
     pub user_email: Option<String>,
     pub last_check: Option<String>,
 }
 
-impl Default for SessionState {
-    fn default() -> Self {
-        Self {
-            authenticated: false,
-            user_email: None,
-            last_check: None,
-        }
-    }
-}
-
 pub struct ClaudeSession;
 
 impl ClaudeSession {
@@ -94,6 +84,7 @@ impl ClaudeSession {
     }
 
     /// Get cached session state
+    #[allow(dead_code)]
     pub fn get_cached_state() -> SessionState {
         SESSION_STATE.lock()
             .map(|s| s.clone())
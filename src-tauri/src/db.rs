@@ -1,8 +1,11 @@
-use rusqlite::{Connection, Result as SqliteResult};
+use rusqlite::{Connection, Result as SqliteResult, Transaction, TransactionBehavior};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Mutex;
-use crate::parser::{Epic, Ticket};
+use crate::workflow::{ColumnCategory, Workflow};
+use crate::{migrations, search};
+use crate::parser::{criteria_progress, Criterion, Epic, Sprint, SprintState, Ticket};
 
 lazy_static::lazy_static! {
     static ref DB_CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
@@ -29,126 +32,13 @@ pub fn init_database() -> Result<(), String> {
             .map_err(|e| format!("Failed to create data directory: {}", e))?;
     }
 
-    let conn = Connection::open(&db_path)
+    let mut conn = Connection::open(&db_path)
         .map_err(|e| format!("Failed to open database: {}", e))?;
 
-    // Create projects table with indexes
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS projects (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            path TEXT NOT NULL UNIQUE,
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            last_accessed TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create projects table: {}", e))?;
-
-    // Create indexes
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_projects_path ON projects(path)",
-        [],
-    ).map_err(|e| format!("Failed to create path index: {}", e))?;
-
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name)",
-        [],
-    ).map_err(|e| format!("Failed to create name index: {}", e))?;
-
-    // Create app_state table for persisting active project and sidebar state
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS app_state (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create app_state table: {}", e))?;
-
-    // Create project_settings table for counters and per-project settings
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS project_settings (
-            project_path TEXT PRIMARY KEY,
-            epic_counter INTEGER NOT NULL DEFAULT 0,
-            ticket_counter INTEGER NOT NULL DEFAULT 0,
-            total_epics INTEGER NOT NULL DEFAULT 0,
-            completed_epics INTEGER NOT NULL DEFAULT 0,
-            total_tickets INTEGER NOT NULL DEFAULT 0,
-            backlog_tickets INTEGER NOT NULL DEFAULT 0,
-            inprogress_tickets INTEGER NOT NULL DEFAULT 0,
-            done_tickets INTEGER NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create project_settings table: {}", e))?;
-
-    // Create epics table for MD snapshots
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS epics (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            epic_id TEXT NOT NULL UNIQUE,
-            title TEXT NOT NULL,
-            priority TEXT NOT NULL DEFAULT 'P4',
-            scope TEXT,
-            file_path TEXT NOT NULL,
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create epics table: {}", e))?;
-
-    // Create tickets table for MD snapshots
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS tickets (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            ticket_id TEXT NOT NULL UNIQUE,
-            epic_id TEXT,
-            title TEXT NOT NULL,
-            description TEXT,
-            status TEXT NOT NULL,
-            file_path TEXT NOT NULL,
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
-            FOREIGN KEY (epic_id) REFERENCES epics(epic_id)
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create tickets table: {}", e))?;
-
-    // Create indexes for epics and tickets
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_epics_epic_id ON epics(epic_id)",
-        [],
-    ).map_err(|e| format!("Failed to create epics epic_id index: {}", e))?;
-
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_tickets_ticket_id ON tickets(ticket_id)",
-        [],
-    ).map_err(|e| format!("Failed to create tickets ticket_id index: {}", e))?;
-
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_tickets_epic_id ON tickets(epic_id)",
-        [],
-    ).map_err(|e| format!("Failed to create tickets epic_id index: {}", e))?;
+    migrations::run(&mut conn, &db_path)?;
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_tickets_status ON tickets(status)",
-        [],
-    ).map_err(|e| format!("Failed to create tickets status index: {}", e))?;
-
-    // Migrate existing tables: add stats columns if missing
-    let migrations = [
-        "ALTER TABLE project_settings ADD COLUMN total_epics INTEGER NOT NULL DEFAULT 0",
-        "ALTER TABLE project_settings ADD COLUMN completed_epics INTEGER NOT NULL DEFAULT 0",
-        "ALTER TABLE project_settings ADD COLUMN total_tickets INTEGER NOT NULL DEFAULT 0",
-        "ALTER TABLE project_settings ADD COLUMN backlog_tickets INTEGER NOT NULL DEFAULT 0",
-        "ALTER TABLE project_settings ADD COLUMN inprogress_tickets INTEGER NOT NULL DEFAULT 0",
-        "ALTER TABLE project_settings ADD COLUMN done_tickets INTEGER NOT NULL DEFAULT 0",
-        "ALTER TABLE epics ADD COLUMN priority TEXT NOT NULL DEFAULT 'P4'",
-    ];
-
-    for migration in &migrations {
-        let _ = conn.execute(migration, []);
-    }
+    conn.execute_batch("PRAGMA foreign_keys = ON")
+        .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
 
     let mut db_conn = DB_CONNECTION.lock().map_err(|e| e.to_string())?;
     *db_conn = Some(conn);
@@ -165,6 +55,36 @@ where
     f(conn).map_err(|e| format!("Database error: {}", e))
 }
 
+/// Run `f` with the id of the registered project at `project_path`. The
+/// frontend passes either the project root or its `.m2k` folder; both
+/// resolve to the same project.
+pub fn with_project<T, F>(project_path: &str, f: F) -> Result<T, String>
+where
+    F: FnOnce(&Connection, i64) -> SqliteResult<T>,
+{
+    with_connection(|conn| match project_id_for(conn, project_path)? {
+        Some(id) => f(conn, id).map(Ok),
+        None => Ok(Err(format!("Project not registered: {}", project_path))),
+    })?
+}
+
+fn project_id_for(conn: &Connection, project_path: &str) -> SqliteResult<Option<i64>> {
+    let m2k = crate::m2k_dir(project_path.trim_end_matches('/'));
+    let root = m2k.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    let m2k = m2k.to_string_lossy().to_string();
+
+    match conn.query_row(
+        "SELECT id FROM projects WHERE rtrim(path, '/') IN (?1, ?2)
+         ORDER BY length(path) DESC LIMIT 1",
+        [&m2k, &root],
+        |row| row.get::<_, i64>(0),
+    ) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 fn map_row_to_project(row: &rusqlite::Row) -> SqliteResult<Project> {
     Ok(Project {
         id: row.get(0)?,
@@ -175,7 +95,19 @@ fn map_row_to_project(row: &rusqlite::Row) -> SqliteResult<Project> {
     })
 }
 
+const TICKET_COLUMNS: &str =
+    "ticket_id, epic_id, title, description, status, file_path, assignee, labels, estimate, due_date, extra,
+     technical_notes, testing, sections,
+     (SELECT json_group_array(depends_on) FROM ticket_dependencies d
+      WHERE d.project_id = tickets.project_id AND d.ticket_id = tickets.ticket_id),
+     criteria, sprint_id";
+
 fn map_row_to_ticket(row: &rusqlite::Row) -> SqliteResult<Ticket> {
+    let criteria: Vec<Criterion> = row
+        .get::<_, Option<String>>(15)?
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+
     Ok(Ticket {
         id: row.get(0)?,
         epic: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
@@ -183,22 +115,64 @@ fn map_row_to_ticket(row: &rusqlite::Row) -> SqliteResult<Ticket> {
         description: row.get(3)?,
         status: row.get(4)?,
         file_path: row.get(5)?,
-        criteria: Vec::new(),
+        sprint: row.get(16)?,
+        criteria_progress: criteria_progress(&criteria),
+        criteria,
+        assignee: row.get(6)?,
+        labels: labels_from_sql(row.get(7)?),
+        estimate: row.get(8)?,
+        due_date: row.get(9)?,
+        extra: extra_from_sql(row.get(10)?),
+        technical_notes: row.get::<_, Option<String>>(11)?.unwrap_or_default(),
+        testing: row.get::<_, Option<String>>(12)?.unwrap_or_default(),
+        sections: row
+            .get::<_, Option<String>>(13)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        dependencies: labels_from_sql(row.get(14)?),
     })
 }
 
-fn execute_ticket_insert(conn: &Connection, ticket: &Ticket, use_replace: bool) -> SqliteResult<usize> {
+fn labels_to_sql(labels: &[String]) -> Option<String> {
+    if labels.is_empty() {
+        None
+    } else {
+        serde_json::to_string(labels).ok()
+    }
+}
+
+fn labels_from_sql(raw: Option<String>) -> Vec<String> {
+    raw.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn extra_to_sql(extra: &serde_json::Map<String, serde_json::Value>) -> Option<String> {
+    if extra.is_empty() {
+        None
+    } else {
+        serde_json::to_string(extra).ok()
+    }
+}
+
+fn extra_from_sql(raw: Option<String>) -> serde_json::Map<String, serde_json::Value> {
+    raw.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default()
+}
+
+fn execute_ticket_insert(conn: &Connection, project_id: i64, ticket: &Ticket, use_replace: bool) -> SqliteResult<usize> {
     let sql = if use_replace {
         "INSERT OR REPLACE INTO tickets
-         (ticket_id, epic_id, title, description, status, file_path, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))"
+         (ticket_id, epic_id, title, description, status, file_path,
+          assignee, labels, estimate, due_date, extra,
+          technical_notes, testing, sections, criteria, project_id, sprint_id, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, datetime('now'))"
     } else {
         "INSERT INTO tickets
-         (ticket_id, epic_id, title, description, status, file_path, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))"
+         (ticket_id, epic_id, title, description, status, file_path,
+          assignee, labels, estimate, due_date, extra,
+          technical_notes, testing, sections, criteria, project_id, sprint_id, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, datetime('now'))"
     };
 
-    conn.execute(
+    let inserted = conn.execute(
         sql,
         rusqlite::params![
             ticket.id,
@@ -206,7 +180,108 @@ fn execute_ticket_insert(conn: &Connection, ticket: &Ticket, use_replace: bool)
             ticket.title,
             ticket.description,
             ticket.status,
-            ticket.file_path
+            ticket.file_path,
+            ticket.assignee,
+            labels_to_sql(&ticket.labels),
+            ticket.estimate,
+            ticket.due_date,
+            extra_to_sql(&ticket.extra),
+            ticket.technical_notes,
+            ticket.testing,
+            if ticket.sections.is_empty() {
+                None
+            } else {
+                serde_json::to_string(&ticket.sections).ok()
+            },
+            if ticket.criteria.is_empty() {
+                None
+            } else {
+                serde_json::to_string(&ticket.criteria).ok()
+            },
+            project_id,
+            ticket.sprint
+        ],
+    )?;
+
+    replace_ticket_dependencies(conn, project_id, &ticket.id, &ticket.dependencies)?;
+    search::index_ticket(conn, project_id, ticket)?;
+    Ok(inserted)
+}
+
+fn replace_ticket_dependencies(
+    conn: &Connection,
+    project_id: i64,
+    ticket_id: &str,
+    dependencies: &[String],
+) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM ticket_dependencies WHERE project_id = ?1 AND ticket_id = ?2",
+        rusqlite::params![project_id, ticket_id],
+    )?;
+
+    for depends_on in dependencies {
+        conn.execute(
+            "INSERT OR IGNORE INTO ticket_dependencies (project_id, ticket_id, depends_on) VALUES (?1, ?2, ?3)",
+            rusqlite::params![project_id, ticket_id, depends_on],
+        )?;
+    }
+    Ok(())
+}
+
+fn execute_epic_insert(
+    conn: &Connection,
+    project_id: i64,
+    epic: &Epic,
+    file_path: &str,
+    use_replace: bool,
+) -> SqliteResult<usize> {
+    let sql = if use_replace {
+        "INSERT OR REPLACE INTO epics
+         (epic_id, title, priority, scope, file_path,
+          assignee, labels, estimate, due_date, extra, project_id, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, datetime('now'))"
+    } else {
+        "INSERT INTO epics
+         (epic_id, title, priority, scope, file_path,
+          assignee, labels, estimate, due_date, extra, project_id, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, datetime('now'))"
+    };
+
+    let inserted = conn.execute(
+        sql,
+        rusqlite::params![
+            epic.id,
+            epic.title,
+            epic.priority,
+            epic.scope,
+            file_path,
+            epic.assignee,
+            labels_to_sql(&epic.labels),
+            epic.estimate,
+            epic.due_date,
+            extra_to_sql(&epic.extra),
+            project_id
+        ],
+    )?;
+
+    search::index_epic(conn, project_id, epic, file_path)?;
+    Ok(inserted)
+}
+
+fn execute_sprint_insert(conn: &Connection, project_id: i64, sprint: &Sprint) -> SqliteResult<usize> {
+    conn.execute(
+        "INSERT OR REPLACE INTO sprints
+         (sprint_id, title, start_date, end_date, goals, state, file_path, project_id, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
+        rusqlite::params![
+            sprint.id,
+            sprint.title,
+            sprint.start_date,
+            sprint.end_date,
+            labels_to_sql(&sprint.goals),
+            sprint.state.as_str(),
+            sprint.file_path,
+            project_id
         ],
     )
 }
@@ -320,6 +395,13 @@ pub struct ProjectSettings {
     pub backlog_tickets: i64,
     pub inprogress_tickets: i64,
     pub done_tickets: i64,
+    pub total_criteria: i64,
+    pub completed_criteria: i64,
+    /// Ticket count per workflow status, including custom columns. Like the
+    /// other counts it is derived from the snapshot on every read.
+    pub status_counts: BTreeMap<String, i64>,
+    /// Sprint currently in progress, if any
+    pub active_sprint: Option<String>,
 }
 
 pub fn init_project_settings(project_path: &str, epic_count: i64, ticket_count: i64) -> Result<(), String> {
@@ -334,73 +416,196 @@ pub fn init_project_settings(project_path: &str, epic_count: i64, ticket_count:
 }
 
 pub fn get_project_settings(project_path: &str) -> Result<Option<ProjectSettings>, String> {
+    let workflow = Workflow::load_or_default(&crate::m2k_dir(project_path));
+
     with_connection(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT project_path, epic_counter, ticket_counter, total_epics, completed_epics,
-                    total_tickets, backlog_tickets, inprogress_tickets, done_tickets
-             FROM project_settings WHERE project_path = ?1"
-        )?;
+        let counters = conn.query_row(
+            "SELECT project_path, epic_counter, ticket_counter FROM project_settings WHERE project_path = ?1",
+            [project_path],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)),
+        );
+        let (path, epic_counter, ticket_counter) = match counters {
+            Ok(counters) => counters,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let (stats, active_sprint) = match project_id_for(conn, project_path)? {
+            Some(project_id) => (
+                snapshot_stats(conn, project_id, &workflow)?,
+                active_sprint(conn, project_id)?,
+            ),
+            None => (ProjectStats::empty(&workflow), None),
+        };
+        let by_category = |category: ColumnCategory| {
+            workflow
+                .columns
+                .iter()
+                .filter(|c| c.category == Some(category))
+                .map(|c| stats.status_counts.get(&c.status).copied().unwrap_or(0))
+                .sum()
+        };
+
+        Ok(Some(ProjectSettings {
+            project_path: path,
+            epic_counter,
+            ticket_counter,
+            total_epics: stats.total_epics,
+            completed_epics: stats.completed_epics,
+            total_tickets: stats.total_tickets,
+            backlog_tickets: by_category(ColumnCategory::Todo),
+            inprogress_tickets: by_category(ColumnCategory::Active),
+            done_tickets: by_category(ColumnCategory::Done),
+            total_criteria: stats.total_criteria,
+            completed_criteria: stats.completed_criteria,
+            status_counts: stats.status_counts,
+            active_sprint,
+        }))
+    })
+}
 
-        match stmt.query_row([project_path], |row| {
-            Ok(ProjectSettings {
-                project_path: row.get(0)?,
-                epic_counter: row.get(1)?,
-                ticket_counter: row.get(2)?,
-                total_epics: row.get(3)?,
-                completed_epics: row.get(4)?,
-                total_tickets: row.get(5)?,
-                backlog_tickets: row.get(6)?,
-                inprogress_tickets: row.get(7)?,
-                done_tickets: row.get(8)?,
-            })
-        }) {
-            Ok(settings) => Ok(Some(settings)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(e),
+/// Counts behind the project statistics. Computed on demand from the
+/// snapshot tables (or from the files), never stored.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStats {
+    pub total_epics: i64,
+    /// Epics with at least one ticket, all of them done
+    pub completed_epics: i64,
+    pub total_tickets: i64,
+    pub status_counts: BTreeMap<String, i64>,
+    pub total_criteria: i64,
+    pub completed_criteria: i64,
+}
+
+impl ProjectStats {
+    /// All zero, with every workflow status present.
+    pub fn empty(workflow: &Workflow) -> Self {
+        Self {
+            total_epics: 0,
+            completed_epics: 0,
+            total_tickets: 0,
+            status_counts: workflow.statuses().map(|s| (s.to_string(), 0)).collect(),
+            total_criteria: 0,
+            completed_criteria: 0,
         }
-    })
+    }
 }
 
-pub fn get_and_increment_epic_counter(project_path: &str) -> Result<i64, String> {
-    with_connection(|conn| {
-        // Get current counter
-        let current: i64 = conn.query_row(
-            "SELECT epic_counter FROM project_settings WHERE project_path = ?1",
-            [project_path],
-            |row| row.get(0),
-        )?;
+/// The workflow's done statuses as a JSON array, for `json_each` in SQL.
+fn done_statuses_json(workflow: &Workflow) -> String {
+    serde_json::to_string(&workflow.statuses().filter(|s| workflow.is_done(s)).collect::<Vec<_>>())
+        .unwrap_or_else(|_| "[]".to_string())
+}
 
-        let next = current + 1;
+fn snapshot_stats(conn: &Connection, project_id: i64, workflow: &Workflow) -> SqliteResult<ProjectStats> {
+    let done_statuses = done_statuses_json(workflow);
+
+    let mut stats = ProjectStats::empty(workflow);
+
+    (stats.total_epics, stats.completed_epics) = conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(
+                    EXISTS (SELECT 1 FROM tickets t
+                            WHERE t.project_id = e.project_id AND t.epic_id = e.epic_id)
+                    AND NOT EXISTS (SELECT 1 FROM tickets t
+                            WHERE t.project_id = e.project_id AND t.epic_id = e.epic_id
+                              AND t.status NOT IN (SELECT value FROM json_each(?2)))
+                ), 0)
+         FROM epics e WHERE e.project_id = ?1",
+        rusqlite::params![project_id, done_statuses],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let mut stmt = conn.prepare(
+        "SELECT status, COUNT(*) FROM tickets WHERE project_id = ?1 GROUP BY status",
+    )?;
+    let counts = stmt.query_map([project_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+    for count in counts {
+        let (status, count) = count?;
+        stats.total_tickets += count;
+        stats.status_counts.insert(status, count);
+    }
 
-        // Increment counter
-        conn.execute(
-            "UPDATE project_settings SET epic_counter = ?1, updated_at = datetime('now') WHERE project_path = ?2",
-            rusqlite::params![next, project_path],
-        )?;
+    (stats.total_criteria, stats.completed_criteria) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(json_extract(c.value, '$.checked')), 0)
+         FROM tickets t, json_each(t.criteria) c
+         WHERE t.project_id = ?1 AND json_extract(c.value, '$.checked') IS NOT NULL",
+        [project_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
 
-        Ok(next)
-    })
+    Ok(stats)
+}
+
+pub fn get_snapshot_stats(project_path: &str, workflow: &Workflow) -> Result<ProjectStats, String> {
+    with_project(project_path, |conn, project_id| snapshot_stats(conn, project_id, workflow))
+}
+
+fn active_sprint(conn: &Connection, project_id: i64) -> SqliteResult<Option<String>> {
+    match conn.query_row(
+        "SELECT sprint_id FROM sprints WHERE project_id = ?1 AND state = 'active'
+         ORDER BY start_date DESC LIMIT 1",
+        [project_id],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum IdCounter {
+    Epic,
+    Ticket,
 }
 
-pub fn get_and_increment_ticket_counter(project_path: &str) -> Result<i64, String> {
+impl IdCounter {
+    fn column(self) -> &'static str {
+        match self {
+            IdCounter::Epic => "epic_counter",
+            IdCounter::Ticket => "ticket_counter",
+        }
+    }
+}
+
+/// Hand out the next number for `counter`, above both the stored counter and
+/// `highest_on_disk`, in a single immediate transaction. `claim` is offered
+/// candidate numbers until it returns `true`; the counter only advances once
+/// a number has been claimed, and an error from `claim` leaves it untouched.
+pub fn allocate_id<F>(project_path: &str, counter: IdCounter, highest_on_disk: i64, mut claim: F) -> Result<i64, String>
+where
+    F: FnMut(i64) -> Result<bool, String>,
+{
+    let column = counter.column();
     with_connection(|conn| {
-        // Get current counter
-        let current: i64 = conn.query_row(
-            "SELECT ticket_counter FROM project_settings WHERE project_path = ?1",
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        let current: i64 = tx.query_row(
+            &format!("SELECT {} FROM project_settings WHERE project_path = ?1", column),
             [project_path],
             |row| row.get(0),
         )?;
 
-        let next = current + 1;
+        let mut next = current.max(highest_on_disk) + 1;
+        loop {
+            match claim(next) {
+                Ok(true) => break,
+                Ok(false) => next += 1,
+                Err(e) => return Ok(Err(e)),
+            }
+        }
 
-        // Increment counter
-        conn.execute(
-            "UPDATE project_settings SET ticket_counter = ?1, updated_at = datetime('now') WHERE project_path = ?2",
+        tx.execute(
+            &format!(
+                "UPDATE project_settings SET {} = ?1, updated_at = datetime('now') WHERE project_path = ?2",
+                column
+            ),
             rusqlite::params![next, project_path],
         )?;
-
-        Ok(next)
-    })
+        tx.commit()?;
+        Ok(Ok(next))
+    })?
 }
 
 pub fn update_project_counters(project_path: &str, epic_counter: i64, ticket_counter: i64) -> Result<(), String> {
@@ -413,140 +618,335 @@ pub fn update_project_counters(project_path: &str, epic_counter: i64, ticket_cou
     })
 }
 
-pub fn increment_epic_stats(project_path: &str) -> Result<(), String> {
-    with_connection(|conn| {
-        conn.execute(
-            "UPDATE project_settings
-             SET total_epics = total_epics + 1,
-                 updated_at = datetime('now')
-             WHERE project_path = ?1",
-            [project_path],
-        )?;
-        Ok(())
-    })
+// Backup path management
+pub fn set_backup_path(project_path: &str, backup_path: &str) -> Result<(), String> {
+    set_app_state(&format!("backup_path:{}", project_path), backup_path)
 }
 
-pub fn increment_ticket_stats(project_path: &str, status: &str) -> Result<(), String> {
-    let status_col = match status {
-        "backlog" => "backlog_tickets",
-        "in_progress" => "inprogress_tickets",
-        "done" => "done_tickets",
-        _ => return Err(format!("Invalid status: {}", status)),
-    };
+pub fn get_backup_path(project_path: &str) -> Result<Option<String>, String> {
+    get_app_state(&format!("backup_path:{}", project_path))
+}
+
+// Dependency enforcement when starting work: "block", "warn" or "off"
+pub fn set_dependency_policy(project_path: &str, policy: &str) -> Result<(), String> {
+    if !matches!(policy, "block" | "warn" | "off") {
+        return Err(format!("Invalid dependency policy: {}", policy));
+    }
+    set_app_state(&format!("dependency_policy:{}", project_path), policy)
+}
+
+pub fn get_dependency_policy(project_path: &str) -> Result<String, String> {
+    Ok(get_app_state(&format!("dependency_policy:{}", project_path))?
+        .unwrap_or_else(|| "warn".to_string()))
+}
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusOverride {
+    pub id: i64,
+    pub ticket_id: String,
+    pub from_status: String,
+    pub to_status: String,
+    pub reason: String,
+    pub created_at: String,
+}
+
+/// Remember that a move was forced past a transition rule, WIP limit or
+/// blocker check.
+fn record_status_override(
+    conn: &Connection,
+    project_path: &str,
+    ticket_id: &str,
+    from_status: &str,
+    to_status: &str,
+    reason: &str,
+) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT INTO status_overrides (project_path, ticket_id, from_status, to_status, reason)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        [project_path, ticket_id, from_status, to_status, reason],
+    )?;
+    Ok(())
+}
+
+pub fn get_status_overrides(project_path: &str) -> Result<Vec<StatusOverride>, String> {
     with_connection(|conn| {
-        conn.execute(
-            &format!(
-                "UPDATE project_settings
-                 SET total_tickets = total_tickets + 1,
-                     {} = {} + 1,
-                     updated_at = datetime('now')
-                 WHERE project_path = ?1",
-                status_col, status_col
-            ),
-            [project_path],
+        let mut stmt = conn.prepare(
+            "SELECT id, ticket_id, from_status, to_status, reason, created_at
+             FROM status_overrides WHERE project_path = ?1
+             ORDER BY id DESC"
         )?;
-        Ok(())
+
+        let overrides = stmt.query_map([project_path], |row| {
+            Ok(StatusOverride {
+                id: row.get(0)?,
+                ticket_id: row.get(1)?,
+                from_status: row.get(2)?,
+                to_status: row.get(3)?,
+                reason: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?;
+
+        overrides.collect()
     })
 }
 
-pub fn update_ticket_status_stats(
-    project_path: &str,
-    old_status: &str,
-    new_status: &str,
-) -> Result<(), String> {
-    if old_status == new_status {
-        return Ok(());
+/// Where a recorded ticket change came from.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSource {
+    /// A command invoked from the board
+    Ui,
+    /// A ticket file created, moved or removed while the app was watching
+    Watcher,
+    /// A ticket file edited outside the app, or a difference found by a full
+    /// sync
+    ExternalEdit,
+    /// A change made on behalf of a Claude task
+    AiTask,
+}
+
+impl EventSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventSource::Ui => "ui",
+            EventSource::Watcher => "watcher",
+            EventSource::ExternalEdit => "external_edit",
+            EventSource::AiTask => "ai_task",
+        }
     }
+}
 
-    let old_col = match old_status {
-        "backlog" => "backlog_tickets",
-        "in_progress" => "inprogress_tickets",
-        "done" => "done_tickets",
-        _ => return Err(format!("Invalid old_status: {}", old_status)),
-    };
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketEvent {
+    pub id: i64,
+    pub ticket_id: String,
+    /// One of "created", "status_changed", "title_changed", "epic_changed",
+    /// "sprint_changed", "renumbered" or "deleted"
+    pub event_type: String,
+    pub from_value: Option<String>,
+    pub to_value: Option<String>,
+    pub source: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityPage {
+    /// Newest first
+    pub events: Vec<TicketEvent>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// The snapshot fields whose changes end up in the ticket history.
+struct TrackedTicket {
+    title: String,
+    epic: String,
+    sprint: String,
+    status: String,
+}
+
+const TRACKED_COLUMNS: &str = "ticket_id, title, COALESCE(epic_id, ''), COALESCE(sprint_id, ''), status";
+
+fn map_row_to_tracked(row: &rusqlite::Row) -> SqliteResult<(String, TrackedTicket)> {
+    Ok((
+        row.get(0)?,
+        TrackedTicket {
+            title: row.get(1)?,
+            epic: row.get(2)?,
+            sprint: row.get(3)?,
+            status: row.get(4)?,
+        },
+    ))
+}
+
+fn record_ticket_event(
+    conn: &Connection,
+    project_id: i64,
+    ticket_id: &str,
+    event_type: &str,
+    from_value: Option<&str>,
+    to_value: Option<&str>,
+    source: EventSource,
+) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT INTO ticket_events (project_id, ticket_id, event_type, from_value, to_value, source)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![project_id, ticket_id, event_type, from_value, to_value, source.as_str()],
+    )?;
+    Ok(())
+}
+
+fn tracked_ticket(conn: &Connection, project_id: i64, ticket_id: &str) -> SqliteResult<Option<TrackedTicket>> {
+    match conn.query_row(
+        &format!(
+            "SELECT {} FROM tickets WHERE project_id = ?1 AND ticket_id = ?2",
+            TRACKED_COLUMNS
+        ),
+        rusqlite::params![project_id, ticket_id],
+        map_row_to_tracked,
+    ) {
+        Ok((_, tracked)) => Ok(Some(tracked)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn tracked_tickets(conn: &Connection, project_id: i64) -> SqliteResult<BTreeMap<String, TrackedTicket>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM tickets WHERE project_id = ?1",
+        TRACKED_COLUMNS
+    ))?;
+    let rows = stmt.query_map([project_id], map_row_to_tracked)?;
+    rows.collect()
+}
 
-    let new_col = match new_status {
-        "backlog" => "backlog_tickets",
-        "in_progress" => "inprogress_tickets",
-        "done" => "done_tickets",
-        _ => return Err(format!("Invalid new_status: {}", new_status)),
+/// Record how `ticket` differs from what the snapshot held before. A ticket
+/// the snapshot did not know is recorded as created.
+fn record_ticket_changes(
+    conn: &Connection,
+    project_id: i64,
+    before: Option<&TrackedTicket>,
+    ticket: &Ticket,
+    source: EventSource,
+) -> SqliteResult<()> {
+    let Some(before) = before else {
+        return record_ticket_event(conn, project_id, &ticket.id, "created", None, Some(&ticket.status), source);
     };
 
-    with_connection(|conn| {
-        conn.execute(
-            &format!(
-                "UPDATE project_settings
-                 SET {} = {} - 1,
-                     {} = {} + 1,
-                     updated_at = datetime('now')
-                 WHERE project_path = ?1",
-                old_col, old_col, new_col, new_col
-            ),
-            [project_path],
-        )?;
-        Ok(())
+    let non_empty = |s: &str| Some(s.to_string()).filter(|s| !s.is_empty());
+    let sprint = ticket.sprint.clone().unwrap_or_default();
+    let changes = [
+        ("status_changed", &before.status, &ticket.status),
+        ("title_changed", &before.title, &ticket.title),
+        ("epic_changed", &before.epic, &ticket.epic),
+        ("sprint_changed", &before.sprint, &sprint),
+    ];
+    for (event_type, from, to) in changes {
+        if from != to {
+            record_ticket_event(
+                conn,
+                project_id,
+                &ticket.id,
+                event_type,
+                non_empty(from).as_deref(),
+                non_empty(to).as_deref(),
+                source,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn map_row_to_ticket_event(row: &rusqlite::Row) -> SqliteResult<TicketEvent> {
+    Ok(TicketEvent {
+        id: row.get(0)?,
+        ticket_id: row.get(1)?,
+        event_type: row.get(2)?,
+        from_value: row.get(3)?,
+        to_value: row.get(4)?,
+        source: row.get(5)?,
+        created_at: row.get(6)?,
     })
 }
 
-pub fn update_epic_completion_stats(
-    project_path: &str,
-    completed_delta: i64,
-) -> Result<(), String> {
-    with_connection(|conn| {
-        conn.execute(
-            "UPDATE project_settings
-             SET completed_epics = completed_epics + ?1,
-                 updated_at = datetime('now')
-             WHERE project_path = ?2",
-            rusqlite::params![completed_delta, project_path],
+/// Every recorded event of one ticket, oldest first.
+pub fn get_ticket_history(project_path: &str, ticket_id: &str) -> Result<Vec<TicketEvent>, String> {
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(
+            "SELECT id, ticket_id, event_type, from_value, to_value, source, created_at
+             FROM ticket_events WHERE project_id = ?1 AND ticket_id = ?2
+             ORDER BY id",
         )?;
-        Ok(())
+        let events = stmt.query_map(rusqlite::params![project_id, ticket_id], map_row_to_ticket_event)?;
+        events.collect()
     })
 }
 
-// Backup path management
-pub fn set_backup_path(project_path: &str, backup_path: &str) -> Result<(), String> {
-    set_app_state(&format!("backup_path:{}", project_path), backup_path)
+/// Every recorded event in the project, oldest first. This is the timeline
+/// analytics is computed from.
+pub fn get_project_events(project_path: &str) -> Result<Vec<TicketEvent>, String> {
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(
+            "SELECT id, ticket_id, event_type, from_value, to_value, source, created_at
+             FROM ticket_events WHERE project_id = ?1
+             ORDER BY id",
+        )?;
+        let events = stmt.query_map([project_id], map_row_to_ticket_event)?;
+        events.collect()
+    })
 }
 
-pub fn get_backup_path(project_path: &str) -> Result<Option<String>, String> {
-    get_app_state(&format!("backup_path:{}", project_path))
+/// One page of the project-wide activity feed, newest first.
+pub fn get_activity_feed(project_path: &str, limit: i64, offset: i64) -> Result<ActivityPage, String> {
+    let limit = limit.clamp(1, 500);
+    let offset = offset.max(0);
+
+    with_project(project_path, |conn, project_id| {
+        let total = conn.query_row(
+            "SELECT COUNT(*) FROM ticket_events WHERE project_id = ?1",
+            [project_id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, ticket_id, event_type, from_value, to_value, source, created_at
+             FROM ticket_events WHERE project_id = ?1
+             ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+        )?;
+        let events = stmt
+            .query_map(rusqlite::params![project_id, limit, offset], map_row_to_ticket_event)?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(ActivityPage {
+            events,
+            total,
+            limit,
+            offset,
+        })
+    })
 }
 
 // Epic and Ticket snapshot CRUD operations
-pub fn upsert_epic(epic: &Epic, file_path: &str) -> Result<(), String> {
-    with_connection(|conn| {
-        conn.execute(
-            "INSERT OR REPLACE INTO epics (epic_id, title, priority, scope, file_path, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
-            rusqlite::params![epic.id, epic.title, epic.priority, epic.scope, file_path],
-        )?;
+pub fn upsert_epic(project_path: &str, epic: &Epic, file_path: &str) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        execute_epic_insert(conn, project_id, epic, file_path, true)?;
         Ok(())
     })
 }
 
-pub fn upsert_ticket(ticket: &Ticket) -> Result<(), String> {
-    with_connection(|conn| {
-        execute_ticket_insert(conn, ticket, true)?;
-        Ok(())
+pub fn upsert_ticket(project_path: &str, ticket: &Ticket, source: EventSource) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        let before = tracked_ticket(conn, project_id, &ticket.id)?;
+        execute_ticket_insert(conn, project_id, ticket, true)?;
+        record_ticket_changes(conn, project_id, before.as_ref(), ticket, source)
     })
 }
 
 pub fn get_all_epics_snapshot(project_path: &str) -> Result<Vec<Epic>, String> {
-    with_connection(|conn| {
+    with_project(project_path, |conn, project_id| {
         let mut stmt = conn.prepare(
-            "SELECT epic_id, title, scope, COALESCE(priority, 'P4') FROM epics WHERE file_path LIKE ?1"
+            "SELECT epic_id, title, scope, COALESCE(priority, 'P4'),
+                    assignee, labels, estimate, due_date, extra
+             FROM epics WHERE project_id = ?1"
         )?;
 
-        let pattern = format!("{}%", project_path);
-        let epics = stmt.query_map([pattern], |row| {
+        let epics = stmt.query_map([project_id], |row| {
             Ok(Epic {
                 id: row.get(0)?,
                 title: row.get(1)?,
                 scope: row.get(2)?,
                 priority: row.get(3)?,
                 tickets: Vec::new(),
+                assignee: row.get(4)?,
+                labels: labels_from_sql(row.get(5)?),
+                estimate: row.get(6)?,
+                due_date: row.get(7)?,
+                extra: extra_from_sql(row.get(8)?),
             })
         })?;
 
@@ -555,97 +955,376 @@ pub fn get_all_epics_snapshot(project_path: &str) -> Result<Vec<Epic>, String> {
 }
 
 pub fn get_all_tickets_snapshot(project_path: &str) -> Result<Vec<Ticket>, String> {
-    with_connection(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT ticket_id, epic_id, title, description, status, file_path
-             FROM tickets WHERE file_path LIKE ?1"
-        )?;
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tickets WHERE project_id = ?1",
+            TICKET_COLUMNS
+        ))?;
 
-        let pattern = format!("{}%", project_path);
-        let tickets = stmt.query_map([pattern], map_row_to_ticket)?;
+        let tickets = stmt.query_map([project_id], map_row_to_ticket)?;
 
         tickets.collect()
     })
 }
 
-pub fn get_tickets_by_epic(epic_id: &str) -> Result<Vec<Ticket>, String> {
-    with_connection(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT ticket_id, epic_id, title, description, status, file_path
-             FROM tickets WHERE epic_id = ?1"
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketFilter {
+    pub assignee: Option<String>,
+    pub label: Option<String>,
+    pub status: Option<String>,
+    pub epic: Option<String>,
+    pub due_before: Option<String>,
+    pub sprint: Option<String>,
+}
+
+pub fn filter_tickets(project_path: &str, filter: &TicketFilter) -> Result<Vec<Ticket>, String> {
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tickets
+             WHERE project_id = ?1
+               AND (?2 IS NULL OR assignee = ?2)
+               AND (?3 IS NULL OR EXISTS (SELECT 1 FROM json_each(tickets.labels) WHERE value = ?3))
+               AND (?4 IS NULL OR status = ?4)
+               AND (?5 IS NULL OR epic_id = ?5)
+               AND (?6 IS NULL OR (due_date IS NOT NULL AND due_date <= ?6))
+               AND (?7 IS NULL OR sprint_id = ?7)
+             ORDER BY ticket_id",
+            TICKET_COLUMNS
+        ))?;
+
+        let tickets = stmt.query_map(
+            rusqlite::params![
+                project_id,
+                filter.assignee,
+                filter.label,
+                filter.status,
+                filter.epic,
+                filter.due_before,
+                filter.sprint
+            ],
+            map_row_to_ticket,
         )?;
 
-        let tickets = stmt.query_map([epic_id], map_row_to_ticket)?;
+        tickets.collect()
+    })
+}
+
+/// Tickets that `ticket_id` depends on and that are not done yet.
+pub fn get_ticket_blockers(project_path: &str, ticket_id: &str, workflow: &Workflow) -> Result<Vec<Ticket>, String> {
+    let done_statuses = done_statuses_json(workflow);
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tickets
+             WHERE project_id = ?1
+               AND status NOT IN (SELECT value FROM json_each(?3))
+               AND ticket_id IN (SELECT depends_on FROM ticket_dependencies
+                                 WHERE project_id = ?1 AND ticket_id = ?2)
+             ORDER BY ticket_id",
+            TICKET_COLUMNS
+        ))?;
+
+        let tickets = stmt.query_map(rusqlite::params![project_id, ticket_id, done_statuses], map_row_to_ticket)?;
 
         tickets.collect()
     })
 }
 
-pub fn delete_epic(epic_id: &str) -> Result<(), String> {
-    with_connection(|conn| {
-        conn.execute("DELETE FROM epics WHERE epic_id = ?1", [epic_id])?;
+pub fn delete_epic(project_path: &str, epic_id: &str) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        conn.execute(
+            "DELETE FROM epics WHERE project_id = ?1 AND epic_id = ?2",
+            rusqlite::params![project_id, epic_id],
+        )?;
+        search::remove_epic(conn, project_id, epic_id)
+    })
+}
+
+pub fn upsert_sprint(project_path: &str, sprint: &Sprint) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        execute_sprint_insert(conn, project_id, sprint)?;
         Ok(())
     })
 }
 
-pub fn delete_ticket(ticket_id: &str) -> Result<(), String> {
-    with_connection(|conn| {
-        conn.execute("DELETE FROM tickets WHERE ticket_id = ?1", [ticket_id])?;
+pub fn delete_sprint(project_path: &str, sprint_id: &str) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        conn.execute(
+            "DELETE FROM sprints WHERE project_id = ?1 AND sprint_id = ?2",
+            rusqlite::params![project_id, sprint_id],
+        )?;
         Ok(())
     })
 }
 
-pub fn update_ticket_status(ticket_id: &str, new_status: &str, project_path: &str) -> Result<(), String> {
-    // Get current status first
-    let old_status: Option<String> = with_connection(|conn| {
-        conn.query_row(
-            "SELECT status FROM tickets WHERE ticket_id = ?1",
-            [ticket_id],
-            |row| row.get(0),
-        )
-    }).ok();
+/// Sprints ordered by start date; undated sprints come first.
+pub fn get_sprints_snapshot(project_path: &str) -> Result<Vec<Sprint>, String> {
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(
+            "SELECT sprint_id, title, start_date, end_date, goals, state, file_path
+             FROM sprints WHERE project_id = ?1
+             ORDER BY start_date, sprint_id",
+        )?;
 
-    // Update ticket status
-    with_connection(|conn| {
+        let sprints = stmt.query_map([project_id], |row| {
+            Ok(Sprint {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                start_date: row.get(2)?,
+                end_date: row.get(3)?,
+                goals: labels_from_sql(row.get(4)?),
+                state: SprintState::parse(&row.get::<_, String>(5)?).unwrap_or_default(),
+                file_path: row.get(6)?,
+            })
+        })?;
+
+        sprints.collect()
+    })
+}
+
+pub fn delete_ticket(project_path: &str, ticket_id: &str, source: EventSource) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        if let Some(before) = tracked_ticket(conn, project_id, ticket_id)? {
+            record_ticket_event(conn, project_id, ticket_id, "deleted", Some(&before.status), None, source)?;
+        }
         conn.execute(
-            "UPDATE tickets SET status = ?1, updated_at = datetime('now') WHERE ticket_id = ?2",
-            rusqlite::params![new_status, ticket_id],
+            "DELETE FROM ticket_dependencies WHERE project_id = ?1 AND ticket_id = ?2",
+            rusqlite::params![project_id, ticket_id],
         )?;
-        Ok(())
-    })?;
+        conn.execute(
+            "DELETE FROM tickets WHERE project_id = ?1 AND ticket_id = ?2",
+            rusqlite::params![project_id, ticket_id],
+        )?;
+        search::remove_ticket(conn, project_id, ticket_id)
+    })
+}
 
-    // Update stats if status changed
-    if let Some(old) = old_status {
-        if old != new_status {
-            update_ticket_status_stats(project_path, &old, new_status)?;
+/// Snapshot side of renumbering a ticket, in one transaction. `remaining` is
+/// the ticket still using `old_id` when the renumbered file was a duplicate;
+/// otherwise the old row goes away and its history follows the new ID.
+pub fn apply_renumber(
+    project_path: &str,
+    old_id: &str,
+    renamed: &Ticket,
+    remaining: Option<&Ticket>,
+    changed_tickets: &[Ticket],
+    changed_epics: &[(Epic, String)],
+) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        let tx = conn.unchecked_transaction()?;
+        match remaining {
+            Some(ticket) => {
+                execute_ticket_insert(&tx, project_id, ticket, true)?;
+            }
+            None => {
+                tx.execute(
+                    "UPDATE ticket_events SET ticket_id = ?3 WHERE project_id = ?1 AND ticket_id = ?2",
+                    rusqlite::params![project_id, old_id, renamed.id],
+                )?;
+                tx.execute(
+                    "DELETE FROM ticket_dependencies WHERE project_id = ?1 AND ticket_id = ?2",
+                    rusqlite::params![project_id, old_id],
+                )?;
+                tx.execute(
+                    "DELETE FROM tickets WHERE project_id = ?1 AND ticket_id = ?2",
+                    rusqlite::params![project_id, old_id],
+                )?;
+                search::remove_ticket(&tx, project_id, old_id)?;
+            }
+        }
+
+        execute_ticket_insert(&tx, project_id, renamed, true)?;
+        record_ticket_event(&tx, project_id, &renamed.id, "renumbered", Some(old_id), Some(&renamed.id), EventSource::Ui)?;
+        for ticket in changed_tickets {
+            execute_ticket_insert(&tx, project_id, ticket, true)?;
+        }
+        for (epic, file_path) in changed_epics {
+            execute_epic_insert(&tx, project_id, epic, file_path, true)?;
+        }
+        tx.commit()
+    })
+}
+
+/// Snapshot side of moving a ticket to another epic, in one transaction.
+/// `epics` are the epics whose ticket tables changed.
+pub fn apply_epic_move(project_path: &str, ticket: &Ticket, epics: &[(Epic, String)]) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        let tx = conn.unchecked_transaction()?;
+        let before = tracked_ticket(&tx, project_id, &ticket.id)?;
+        execute_ticket_insert(&tx, project_id, ticket, true)?;
+        record_ticket_changes(&tx, project_id, before.as_ref(), ticket, EventSource::Ui)?;
+        for (epic, file_path) in epics {
+            execute_epic_insert(&tx, project_id, epic, file_path, true)?;
         }
+        tx.commit()
+    })
+}
+
+/// Set the snapshot status of a ticket, recording the change. Returns the
+/// previous status, or `None` if the snapshot has no such ticket.
+fn set_snapshot_status(
+    conn: &Connection,
+    project_id: i64,
+    ticket_id: &str,
+    new_status: &str,
+    source: EventSource,
+) -> SqliteResult<Option<String>> {
+    let Some(before) = tracked_ticket(conn, project_id, ticket_id)? else {
+        return Ok(None);
+    };
+
+    if before.status != new_status {
+        conn.execute(
+            "UPDATE tickets SET status = ?1, updated_at = datetime('now')
+             WHERE project_id = ?2 AND ticket_id = ?3",
+            rusqlite::params![new_status, project_id, ticket_id],
+        )?;
+        record_ticket_event(
+            conn,
+            project_id,
+            ticket_id,
+            "status_changed",
+            Some(&before.status),
+            Some(new_status),
+            source,
+        )?;
     }
+    Ok(Some(before.status))
+}
 
-    Ok(())
+/// Update the status of a known ticket. Fails if the project has no snapshot
+/// of `ticket_id`, so callers can fall back to a full upsert.
+pub fn update_ticket_status(
+    project_path: &str,
+    ticket_id: &str,
+    new_status: &str,
+    source: EventSource,
+) -> Result<(), String> {
+    with_project(project_path, |conn, project_id| {
+        set_snapshot_status(conn, project_id, ticket_id, new_status, source)
+    })?
+    .map(|_| ())
+    .ok_or(format!("Ticket {} not found", ticket_id))
+}
+
+/// A move that broke a transition, WIP or dependency rule and was forced.
+pub struct ForcedMove<'a> {
+    pub from_status: &'a str,
+    pub reason: &'a str,
+}
+
+/// Record a status change and run `apply` (the file move) in one
+/// transaction. If `apply` fails, the snapshot, history and any forced-move
+/// record are left as they were. Holding the connection also keeps the
+/// watcher from recording the same move a second time.
+pub fn change_ticket_status<F>(
+    project_path: &str,
+    ticket_id: &str,
+    new_status: &str,
+    source: EventSource,
+    forced: Option<ForcedMove>,
+    apply: F,
+) -> Result<(), String>
+where
+    F: FnOnce() -> Result<(), String>,
+{
+    with_connection(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        if let Some(project_id) = project_id_for(&tx, project_path)? {
+            set_snapshot_status(&tx, project_id, ticket_id, new_status, source)?;
+        }
+        if let Err(e) = apply() {
+            return Ok(Err(e));
+        }
+        if let Some(forced) = forced {
+            record_status_override(&tx, project_path, ticket_id, forced.from_status, new_status, forced.reason)?;
+        }
+        tx.commit()?;
+        Ok(Ok(()))
+    })?
+}
+
+/// Refresh the search entry of one resource file after it changed on disk.
+pub fn reindex_resource(project_path: &str, path: &std::path::Path) -> Result<(), String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    with_project(project_path, |conn, project_id| {
+        search::index_resource(conn, project_id, &m2k_path, path)
+    })
+}
+
+/// Several files claiming the same ticket ID. Sync keeps the first one, in
+/// workflow column order.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IdCollision {
+    pub id: String,
+    pub files: Vec<String>,
 }
 
-pub fn sync_md_snapshots(project_path: &str) -> Result<(), String> {
-    use crate::parser::{parse_epics, parse_tickets};
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub collisions: Vec<IdCollision>,
+}
+
+/// Split `tickets` into one ticket per ID and the IDs claimed by more than one file.
+fn dedupe_tickets(tickets: Vec<Ticket>) -> (Vec<Ticket>, Vec<IdCollision>) {
+    let mut files: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut unique = Vec::with_capacity(tickets.len());
+    for ticket in tickets {
+        let claimed = files.entry(ticket.id.clone()).or_default();
+        claimed.push(ticket.file_path.clone());
+        if claimed.len() == 1 {
+            unique.push(ticket);
+        }
+    }
+    let collisions = files
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(id, files)| IdCollision { id, files })
+        .collect();
+    (unique, collisions)
+}
+
+pub fn sync_md_snapshots(project_path: &str) -> Result<SyncReport, String> {
+    use crate::parser::{find_epic_file, highest_file_number, parse_epics, parse_sprints, parse_tickets};
+
+    let m2k_path = crate::m2k_dir(project_path);
+    let workflow = Workflow::load_or_default(&m2k_path);
+
+    // Reservations abandoned by a crashed or closed editor would otherwise
+    // keep their IDs forever
+    for path in crate::ids::sweep_reservations(&m2k_path, &workflow) {
+        log::info!("Released stale ID reservation {}", path.display());
+    }
 
     // Parse all MD files
-    let m2k_path = std::path::Path::new(project_path).join(".m2k");
-    let epics = parse_epics(m2k_path.to_str().ok_or("Invalid path")?)?;
-    let tickets = parse_tickets(m2k_path.to_str().ok_or("Invalid path")?)?;
+    let m2k_str = m2k_path.to_str().ok_or("Invalid path")?;
+    let epics = parse_epics(m2k_str)?;
+    let (tickets, collisions) = dedupe_tickets(parse_tickets(m2k_str)?);
+    let sprints = parse_sprints(m2k_str)?;
+    for collision in &collisions {
+        log::warn!("Ticket ID {} is used by {}", collision.id, collision.files.join(", "));
+    }
+
+    // IDs created outside the app must not be handed out again
+    let highest_ticket = highest_file_number(workflow.ticket_folders().map(|f| m2k_path.join(f)), "T-");
+    let highest_epic = highest_file_number([m2k_path.join("epics")], "EPIC-");
+
+    with_project(project_path, |conn, project_id| {
+        // Differences to the previous snapshot go into the ticket history
+        let before = tracked_tickets(conn, project_id)?;
 
-    with_connection(|conn| {
         // Start transaction for atomicity
         conn.execute("BEGIN TRANSACTION", [])?;
 
         // Delete existing snapshots for this project
         let delete_result = (|| {
-            conn.execute(
-                "DELETE FROM epics WHERE file_path LIKE ?1",
-                [format!("{}%", project_path)]
-            )?;
-            conn.execute(
-                "DELETE FROM tickets WHERE file_path LIKE ?1",
-                [format!("{}%", project_path)]
-            )?;
+            conn.execute("DELETE FROM ticket_dependencies WHERE project_id = ?1", [project_id])?;
+            conn.execute("DELETE FROM epics WHERE project_id = ?1", [project_id])?;
+            conn.execute("DELETE FROM tickets WHERE project_id = ?1", [project_id])?;
+            conn.execute("DELETE FROM sprints WHERE project_id = ?1", [project_id])?;
+            search::clear_project(conn, project_id)?;
             Ok::<(), rusqlite::Error>(())
         })();
 
@@ -656,16 +1335,10 @@ pub fn sync_md_snapshots(project_path: &str) -> Result<(), String> {
 
         // Bulk insert epics
         for epic in &epics {
-            let insert_result = conn.execute(
-                "INSERT INTO epics (epic_id, title, scope, file_path, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, datetime('now'))",
-                rusqlite::params![
-                    epic.id,
-                    epic.title,
-                    epic.scope,
-                    m2k_path.to_str().unwrap_or("")
-                ],
-            );
+            let file_path = find_epic_file(&m2k_path, &epic.id)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| m2k_str.to_string());
+            let insert_result = execute_epic_insert(conn, project_id, epic, &file_path, false);
 
             if let Err(e) = insert_result {
                 let _ = conn.execute("ROLLBACK", []);
@@ -673,16 +1346,73 @@ pub fn sync_md_snapshots(project_path: &str) -> Result<(), String> {
             }
         }
 
+        for sprint in &sprints {
+            if let Err(e) = execute_sprint_insert(conn, project_id, sprint) {
+                let _ = conn.execute("ROLLBACK", []);
+                return Err(e);
+            }
+        }
+
         // Bulk insert tickets
         for ticket in &tickets {
-            if let Err(e) = execute_ticket_insert(conn, ticket, false) {
+            if let Err(e) = execute_ticket_insert(conn, project_id, ticket, false) {
                 let _ = conn.execute("ROLLBACK", []);
                 return Err(e);
             }
         }
 
+        // A first sync has nothing to compare against
+        if !before.is_empty() {
+            if let Err(e) = record_sync_changes(conn, project_id, &before, &tickets) {
+                let _ = conn.execute("ROLLBACK", []);
+                return Err(e);
+            }
+        }
+
+        // Text resources only live in the search index
+        if let Err(e) = search::index_resources(conn, project_id, &m2k_path) {
+            let _ = conn.execute("ROLLBACK", []);
+            return Err(e);
+        }
+
+        if let Err(e) = conn.execute(
+            "UPDATE project_settings
+             SET epic_counter = MAX(epic_counter, ?1), ticket_counter = MAX(ticket_counter, ?2)
+             WHERE project_path = ?3",
+            rusqlite::params![highest_epic, highest_ticket, project_path],
+        ) {
+            let _ = conn.execute("ROLLBACK", []);
+            return Err(e);
+        }
+
         // Commit transaction
         conn.execute("COMMIT", [])?;
-        Ok(())
+        Ok(SyncReport { collisions })
     })
 }
+
+fn record_sync_changes(
+    conn: &Connection,
+    project_id: i64,
+    before: &BTreeMap<String, TrackedTicket>,
+    tickets: &[Ticket],
+) -> SqliteResult<()> {
+    for ticket in tickets {
+        record_ticket_changes(conn, project_id, before.get(&ticket.id), ticket, EventSource::ExternalEdit)?;
+    }
+
+    for (ticket_id, gone) in before {
+        if !tickets.iter().any(|t| &t.id == ticket_id) {
+            record_ticket_event(
+                conn,
+                project_id,
+                ticket_id,
+                "deleted",
+                Some(&gone.status),
+                None,
+                EventSource::ExternalEdit,
+            )?;
+        }
+    }
+    Ok(())
+}
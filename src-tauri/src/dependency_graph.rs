@@ -0,0 +1,448 @@
+use crate::parser::{self, Ticket};
+use crate::workflow::{ColumnCategory, Workflow};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    pub epic: String,
+    pub status: String,
+    pub estimate: Option<f64>,
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    /// The blocked ticket
+    pub from: String,
+    /// The ticket it depends on
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphReport {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub cycles: Vec<Vec<String>>,
+    /// Dependencies that point at tickets which do not exist
+    pub missing: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedTicket {
+    pub id: String,
+    pub status: String,
+    pub blockers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticalPath {
+    pub epic_id: String,
+    pub tickets: Vec<String>,
+    /// Sum of estimates along the path, counting unestimated tickets as 1
+    pub length: f64,
+}
+
+/// Dependency DAG across every ticket folder, built from `## Dependencies`.
+pub struct DependencyGraph {
+    nodes: BTreeMap<String, GraphNode>,
+    workflow: Workflow,
+}
+
+impl DependencyGraph {
+    pub fn load(project_path: &str) -> Result<Self, String> {
+        let tickets = parser::parse_tickets(project_path)?;
+        let workflow = Workflow::load_or_default(Path::new(project_path));
+        Ok(Self::build(&tickets, workflow))
+    }
+
+    pub fn build(tickets: &[Ticket], workflow: Workflow) -> Self {
+        let nodes = tickets
+            .iter()
+            .map(|t| {
+                (
+                    t.id.clone(),
+                    GraphNode {
+                        id: t.id.clone(),
+                        title: t.title.clone(),
+                        epic: t.epic.clone(),
+                        status: t.status.clone(),
+                        estimate: t.estimate,
+                        depends_on: t.dependencies.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        Self { nodes, workflow }
+    }
+
+    fn is_done(&self, id: &str) -> bool {
+        self.nodes.get(id).is_some_and(|n| self.workflow.is_done(&n.status))
+    }
+
+    pub fn report(&self) -> GraphReport {
+        let mut edges = Vec::new();
+        let mut missing = Vec::new();
+
+        for node in self.nodes.values() {
+            for dep in &node.depends_on {
+                let edge = GraphEdge {
+                    from: node.id.clone(),
+                    to: dep.clone(),
+                };
+                if self.nodes.contains_key(dep) {
+                    edges.push(edge);
+                } else {
+                    missing.push(edge);
+                }
+            }
+        }
+
+        GraphReport {
+            nodes: self.nodes.values().cloned().collect(),
+            edges,
+            cycles: self.cycles(),
+            missing,
+        }
+    }
+
+    /// Blockers of `ticket_id` that are not done yet. References to tickets
+    /// that do not exist are reported by `report()` instead.
+    pub fn unmet_blockers(&self, ticket_id: &str) -> Vec<String> {
+        self.nodes
+            .get(ticket_id)
+            .map(|node| {
+                node.depends_on
+                    .iter()
+                    .filter(|dep| self.nodes.contains_key(*dep) && !self.is_done(dep))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Unfinished tickets waiting on at least one unfinished blocker.
+    pub fn blocked(&self) -> Vec<BlockedTicket> {
+        self.nodes
+            .values()
+            .filter(|node| !self.workflow.is_done(&node.status))
+            .filter_map(|node| {
+                let blockers = self.unmet_blockers(&node.id);
+                if blockers.is_empty() {
+                    None
+                } else {
+                    Some(BlockedTicket {
+                        id: node.id.clone(),
+                        status: node.status.clone(),
+                        blockers,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Tickets in a "todo" column whose blockers are all done.
+    pub fn ready(&self) -> Vec<GraphNode> {
+        self.nodes
+            .values()
+            .filter(|node| {
+                self.workflow.category(&node.status) == Some(ColumnCategory::Todo)
+                    && self.unmet_blockers(&node.id).is_empty()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Strongly connected components with more than one ticket, plus
+    /// tickets that depend on themselves (Tarjan's algorithm). The walk keeps
+    /// its own stack so long dependency chains cannot overflow the thread's.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        struct Tarjan<'a> {
+            graph: &'a DependencyGraph,
+            index: usize,
+            indices: HashMap<&'a str, usize>,
+            lowlink: HashMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            on_stack: HashSet<&'a str>,
+            cycles: Vec<Vec<String>>,
+        }
+
+        impl<'a> Tarjan<'a> {
+            fn enter(&mut self, id: &'a str) {
+                self.indices.insert(id, self.index);
+                self.lowlink.insert(id, self.index);
+                self.index += 1;
+                self.stack.push(id);
+                self.on_stack.insert(id);
+            }
+
+            fn lower(&mut self, id: &'a str, value: usize) {
+                let low = self.lowlink[id].min(value);
+                self.lowlink.insert(id, low);
+            }
+
+            fn visit(&mut self, root: &'a str) {
+                let graph = self.graph;
+                // Tickets being visited, each with the next dependency to look at
+                let mut path: Vec<(&'a str, usize)> = vec![(root, 0)];
+                self.enter(root);
+
+                while let Some((id, next)) = path.last_mut() {
+                    let id: &'a str = id;
+                    let node = &graph.nodes[id];
+
+                    if let Some(dep) = node.depends_on.get(*next) {
+                        *next += 1;
+                        let Some(dep) = graph.nodes.get(dep) else {
+                            continue;
+                        };
+                        let dep = dep.id.as_str();
+                        if !self.indices.contains_key(dep) {
+                            self.enter(dep);
+                            path.push((dep, 0));
+                        } else if self.on_stack.contains(dep) {
+                            self.lower(id, self.indices[dep]);
+                        }
+                        continue;
+                    }
+
+                    path.pop();
+                    if let Some(&(parent, _)) = path.last() {
+                        self.lower(parent, self.lowlink[id]);
+                    }
+
+                    if self.lowlink[id] == self.indices[id] {
+                        let mut component = Vec::new();
+                        while let Some(member) = self.stack.pop() {
+                            self.on_stack.remove(member);
+                            component.push(member.to_string());
+                            if member == id {
+                                break;
+                            }
+                        }
+                        let self_loop = node.depends_on.iter().any(|d| d == id);
+                        if component.len() > 1 || self_loop {
+                            component.sort();
+                            self.cycles.push(component);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indices: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            cycles: Vec::new(),
+        };
+
+        for id in self.nodes.keys() {
+            if !tarjan.indices.contains_key(id.as_str()) {
+                tarjan.visit(id);
+            }
+        }
+
+        tarjan.cycles
+    }
+
+    /// Longest chain of unfinished work inside an epic, weighted by estimate.
+    pub fn critical_path(&self, epic_id: &str) -> Result<CriticalPath, String> {
+        let members: BTreeMap<&str, &GraphNode> = self
+            .nodes
+            .values()
+            .filter(|n| n.epic == epic_id && !self.workflow.is_done(&n.status))
+            .map(|n| (n.id.as_str(), n))
+            .collect();
+
+        // Kahn's algorithm over the epic's subgraph; dependencies come first
+        let mut indegree: HashMap<&str, usize> = members.keys().map(|id| (*id, 0)).collect();
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for node in members.values() {
+            for dep in &node.depends_on {
+                if let Some((dep_id, _)) = members.get_key_value(dep.as_str()) {
+                    *indegree.entry(node.id.as_str()).or_default() += 1;
+                    dependents.entry(dep_id).or_default().push(node.id.as_str());
+                }
+            }
+        }
+
+        let mut queue: Vec<&str> = indegree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        queue.sort();
+
+        let mut order = Vec::new();
+        while let Some(id) = queue.pop() {
+            order.push(id);
+            for next in dependents.get(id).into_iter().flatten() {
+                let degree = indegree.get_mut(next).expect("member indegree");
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push(next);
+                }
+            }
+        }
+
+        if order.len() != members.len() {
+            let mut stuck: Vec<&str> = indegree
+                .iter()
+                .filter(|(_, degree)| **degree > 0)
+                .map(|(id, _)| *id)
+                .collect();
+            stuck.sort();
+            return Err(format!(
+                "Dependency cycle in {} involving: {}",
+                epic_id,
+                stuck.join(", ")
+            ));
+        }
+
+        let weight = |id: &str| members[id].estimate.unwrap_or(1.0).max(0.0);
+        let mut best: HashMap<&str, (f64, Option<&str>)> = HashMap::new();
+        for id in &order {
+            let (length, prev) = members[id]
+                .depends_on
+                .iter()
+                .filter_map(|dep| best.get_key_value(dep.as_str()))
+                .map(|(dep, (length, _))| (*length, Some(*dep)))
+                .fold((0.0, None), |acc, item| if item.0 > acc.0 { item } else { acc });
+            best.insert(id, (length + weight(id), prev));
+        }
+
+        let end = best
+            .iter()
+            .max_by(|a, b| a.1 .0.total_cmp(&b.1 .0).then_with(|| b.0.cmp(a.0)))
+            .map(|(id, (length, _))| (*id, *length));
+
+        let mut tickets = Vec::new();
+        let mut length = 0.0;
+        if let Some((end, total)) = end {
+            length = total;
+            let mut cursor = Some(end);
+            while let Some(id) = cursor {
+                tickets.push(id.to_string());
+                cursor = best[id].1;
+            }
+            tickets.reverse();
+        }
+
+        Ok(CriticalPath {
+            epic_id: epic_id.to_string(),
+            tickets,
+            length,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(id, status, estimate, depends_on)`, all in EPIC-001 on the default workflow.
+    fn graph(tickets: &[(&str, &str, Option<f64>, &[&str])]) -> DependencyGraph {
+        let nodes = tickets
+            .iter()
+            .map(|(id, status, estimate, deps)| {
+                let node = GraphNode {
+                    id: id.to_string(),
+                    title: String::new(),
+                    epic: "EPIC-001".to_string(),
+                    status: status.to_string(),
+                    estimate: *estimate,
+                    depends_on: deps.iter().map(|d| d.to_string()).collect(),
+                };
+                (id.to_string(), node)
+            })
+            .collect();
+        DependencyGraph {
+            nodes,
+            workflow: Workflow::default(),
+        }
+    }
+
+    #[test]
+    fn cycles_finds_components_and_self_loops() {
+        let graph = graph(&[
+            ("T-001", "backlog", None, &["T-002"]),
+            ("T-002", "backlog", None, &["T-003"]),
+            ("T-003", "backlog", None, &["T-001"]),
+            ("T-004", "backlog", None, &["T-004"]),
+            ("T-005", "backlog", None, &["T-001", "T-999"]),
+        ]);
+        assert_eq!(
+            graph.cycles(),
+            vec![
+                vec!["T-001".to_string(), "T-002".to_string(), "T-003".to_string()],
+                vec!["T-004".to_string()],
+            ]
+        );
+        assert_eq!(graph.report().missing.len(), 1);
+    }
+
+    #[test]
+    fn cycles_handles_long_chains() {
+        let ids: Vec<String> = (0..100_000).map(|i| format!("T-{:06}", i)).collect();
+        let deps: Vec<[&str; 1]> = (0..ids.len()).map(|i| [ids[(i + 1) % ids.len()].as_str()]).collect();
+        let tickets: Vec<(&str, &str, Option<f64>, &[&str])> = ids
+            .iter()
+            .zip(&deps)
+            .map(|(id, dep)| (id.as_str(), "backlog", None, &dep[..]))
+            .collect();
+        let cycles = graph(&tickets).cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), ids.len());
+    }
+
+    #[test]
+    fn done_blockers_are_met() {
+        let graph = graph(&[
+            ("T-001", "done", None, &[]),
+            ("T-002", "in_progress", None, &[]),
+            ("T-003", "backlog", None, &["T-001", "T-002", "T-999"]),
+            ("T-004", "backlog", None, &["T-001"]),
+        ]);
+        assert_eq!(graph.unmet_blockers("T-003"), vec!["T-002".to_string()]);
+        let ready: Vec<String> = graph.ready().into_iter().map(|n| n.id).collect();
+        assert_eq!(ready, vec!["T-004".to_string()]);
+    }
+
+    #[test]
+    fn critical_path_follows_the_heaviest_chain() {
+        let graph = graph(&[
+            ("T-001", "backlog", Some(2.0), &[]),
+            ("T-002", "backlog", Some(5.0), &["T-001"]),
+            ("T-003", "backlog", Some(1.0), &["T-001"]),
+            ("T-004", "backlog", None, &["T-002", "T-003"]),
+            ("T-005", "done", Some(8.0), &[]),
+        ]);
+        let path = graph.critical_path("EPIC-001").unwrap();
+        assert_eq!(path.tickets, vec!["T-001", "T-002", "T-004"]);
+        assert_eq!(path.length, 8.0);
+    }
+
+    #[test]
+    fn critical_path_reports_cycles() {
+        let graph = graph(&[
+            ("T-001", "backlog", None, &["T-002"]),
+            ("T-002", "backlog", None, &["T-001"]),
+            ("T-003", "backlog", None, &[]),
+        ]);
+        let err = graph.critical_path("EPIC-001").unwrap_err();
+        assert!(err.contains("T-001, T-002"), "{}", err);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::db;
+use crate::frontmatter;
+use crate::ids;
+use crate::parser::{self, Epic, Ticket};
+use crate::workflow::Workflow;
+use crate::writer::{DocumentBatch, MarkdownDocument};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn epic_files(m2k_path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(m2k_path.join("epics")) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Add or update the row of `ticket` in its epic's table. Cells that already
+/// say the same thing (e.g. `In Progress` for `in_progress`) are left alone.
+fn upsert_row(doc: &mut MarkdownDocument, ticket: &Ticket, workflow: &Workflow) -> Result<(), String> {
+    if let Some((table, index)) = doc.find_row(&ticket.id) {
+        let row = &table.rows[index];
+        let title_column = doc.column_index(&table, "Title").unwrap_or(1);
+        let status_column = doc.column_index(&table, "Status").unwrap_or(2);
+
+        let title = row.cells.get(title_column).map(|cell| doc.cell_text(cell).to_string());
+        if title.is_some_and(|title| title != ticket.title.replace('|', "\\|")) {
+            doc.set_ticket_row_title(&ticket.id, &ticket.title);
+        }
+        let status = row.cells.get(status_column).map(|cell| doc.cell_text(cell).to_string());
+        if status.is_some_and(|status| workflow.status_for_label(&status) != Some(ticket.status.as_str())) {
+            doc.set_ticket_row_status(&ticket.id, &ticket.status);
+        }
+        return Ok(());
+    }
+
+    if let Some(table) = doc.ticket_table() {
+        doc.append_row(&table, &[&ticket.id, &ticket.title, &ticket.status]);
+        return Ok(());
+    }
+
+    // Only create the table where it would not replace other content
+    if doc.section("Tickets").is_some_and(|body| !body.is_empty()) {
+        log::warn!("Tickets section without a table; not adding {}", ticket.id);
+        return Ok(());
+    }
+    doc.set_section(
+        "Tickets",
+        &format!(
+            "| ID | Title | Status |\n|----|-------|--------|\n| {} | {} | {} |",
+            ticket.id,
+            ticket.title.replace('|', "\\|"),
+            ticket.status
+        ),
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpicMoveOutcome {
+    pub ticket_id: String,
+    /// Empty when the ticket had no epic
+    pub from_epic: String,
+    pub to_epic: String,
+    /// The ticket and the epics whose tables changed
+    pub updated_files: Vec<String>,
+}
+
+/// Epic documents that need editing so the tables agree with one ticket.
+/// Only documents that changed are returned.
+fn table_edits(
+    m2k_path: &Path,
+    ticket_id: &str,
+    ticket: Option<&Ticket>,
+    workflow: &Workflow,
+) -> Result<Vec<(PathBuf, MarkdownDocument)>, String> {
+    let mut edits = Vec::new();
+    for path in epic_files(m2k_path) {
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let epic_id = parser::epic_id_from_file_name(stem);
+        let mut doc = MarkdownDocument::load(&path)?;
+
+        match ticket {
+            Some(ticket) if ticket.epic == epic_id => upsert_row(&mut doc, ticket, workflow)?,
+            _ => {
+                while let Some((table, index)) = doc.find_row(ticket_id) {
+                    doc.remove_row(&table.rows[index]);
+                }
+            }
+        }
+
+        if doc.is_dirty() {
+            edits.push((path, doc));
+        }
+    }
+    Ok(edits)
+}
+
+/// Make the epic ticket tables agree with one ticket: its epic lists it with
+/// its current title and status, and no other epic lists it. Pass `None`
+/// once the ticket is gone. Files are only written when they change, so the
+/// watcher does not see edits that would bring it back here.
+pub fn sync_ticket_row(m2k_path: &Path, ticket_id: &str, ticket: Option<&Ticket>) -> Result<Vec<PathBuf>, String> {
+    // Files not named after a plain ID have no row to keep; the linter reports them
+    if !ids::is_ticket_id(ticket_id) {
+        return Ok(Vec::new());
+    }
+
+    let workflow = Workflow::load_or_default(m2k_path);
+    let mut written = Vec::new();
+    for (path, mut doc) in table_edits(m2k_path, ticket_id, ticket, &workflow)? {
+        if doc.save()? {
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+/// Move a ticket to another epic: rewrite its epic reference, move its row
+/// between the epic tables and update the snapshot. Either everything is
+/// applied or the files are restored. Completed-epic counts are derived from
+/// the snapshot, so they follow without further bookkeeping.
+pub fn move_ticket_to_epic(project_path: &str, ticket_id: &str, new_epic_id: &str) -> Result<EpicMoveOutcome, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let workflow = Workflow::load_or_default(&m2k_path);
+
+    let mut sources = workflow
+        .ticket_folders()
+        .map(|folder| m2k_path.join(folder).join(format!("{}.md", ticket_id)))
+        .filter(|path| path.is_file());
+    let source = sources.next().ok_or(format!("Ticket {} not found", ticket_id))?;
+    if sources.next().is_some() {
+        return Err(format!("{} is used by several files; renumber one first", ticket_id));
+    }
+    let epic_path = parser::find_epic_file(&m2k_path, new_epic_id).ok_or(format!("Epic {} not found", new_epic_id))?;
+
+    let status = workflow.status_for_path(&source).unwrap_or_default().to_string();
+    let ticket = parser::parse_ticket_file(&source, &status).ok_or(format!("Failed to read {}", source.display()))?;
+    let mut outcome = EpicMoveOutcome {
+        ticket_id: ticket_id.to_string(),
+        from_epic: ticket.epic.clone(),
+        to_epic: new_epic_id.to_string(),
+        updated_files: Vec::new(),
+    };
+    if ticket.epic == new_epic_id {
+        return Ok(outcome);
+    }
+
+    // The parser prefers the frontmatter key, so keep both places in agreement
+    let mut ticket_doc = MarkdownDocument::load(&source)?;
+    let in_frontmatter = frontmatter::split(ticket_doc.content()).0.and_then(|meta| meta.epic).is_some();
+    if in_frontmatter {
+        ticket_doc.set_frontmatter_field("epic", Some(&serde_json::Value::from(new_epic_id)))?;
+    }
+    if !in_frontmatter || ticket_doc.bold_field("Epic").is_some() {
+        ticket_doc.set_bold_field("Epic", new_epic_id)?;
+    }
+
+    let moved = Ticket { epic: new_epic_id.to_string(), ..ticket };
+    let mut edits = if ids::is_ticket_id(ticket_id) {
+        table_edits(&m2k_path, ticket_id, Some(&moved), &workflow)?
+    } else {
+        Vec::new()
+    };
+
+    let docs = std::iter::once(&mut ticket_doc).chain(edits.iter_mut().map(|(_, doc)| doc));
+    let batch = DocumentBatch::save(docs)?;
+
+    // Snapshot, from the files as written
+    let updated = parser::parse_ticket_file(&source, &status);
+    let epics: Vec<(Epic, String)> = edits
+        .iter()
+        .map(|(path, _)| path)
+        .chain(std::iter::once(&epic_path))
+        .filter_map(|path| Some((parser::parse_epic_file(path)?, path.to_string_lossy().to_string())))
+        .collect();
+    let snapshot_result = match &updated {
+        Some(updated) if updated.epic == new_epic_id => db::apply_epic_move(project_path, updated, &epics),
+        Some(_) => Err(format!("{} still names another epic", source.display())),
+        None => Err(format!("Failed to read {}", source.display())),
+    };
+    if let Err(e) = snapshot_result {
+        batch.rollback();
+        return Err(e);
+    }
+
+    outcome.updated_files = std::iter::once(&source)
+        .chain(edits.iter().map(|(path, _)| path))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    Ok(outcome)
+}
+
+/// `sync_ticket_row` for a ticket file that was just written.
+pub fn sync_ticket_file(m2k_path: &Path, path: &Path) -> Result<Vec<PathBuf>, String> {
+    let workflow = Workflow::load_or_default(m2k_path);
+    let Some(status) = workflow.status_for_path(path) else {
+        return Ok(Vec::new());
+    };
+    match parser::parse_ticket_file(path, status) {
+        Some(ticket) => sync_ticket_row(m2k_path, &ticket.id, Some(&ticket)),
+        None => Ok(Vec::new()),
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Structured metadata read from an optional block at the top of a ticket or
+/// epic file. YAML blocks are fenced with `---`, TOML blocks with `+++`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Frontmatter {
+    pub assignee: Option<String>,
+    pub labels: Vec<String>,
+    pub estimate: Option<f64>,
+    pub due_date: Option<String>,
+    pub epic: Option<String>,
+    pub sprint: Option<String>,
+    pub priority: Option<String>,
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrontmatterFormat {
+    Yaml,
+    Toml,
+}
+
+impl FrontmatterFormat {
+    pub fn fence(&self) -> &'static str {
+        match self {
+            FrontmatterFormat::Yaml => "---",
+            FrontmatterFormat::Toml => "+++",
+        }
+    }
+}
+
+/// Byte layout of a frontmatter block inside a file.
+#[derive(Debug, Clone)]
+pub struct FrontmatterBlock {
+    pub format: FrontmatterFormat,
+    /// Range of the raw metadata between the fences
+    pub inner: std::ops::Range<usize>,
+    /// Offset of the first byte after the closing fence line
+    pub body_start: usize,
+}
+
+/// Locate a frontmatter block. Only a fence on the very first line counts.
+pub fn locate(content: &str) -> Option<FrontmatterBlock> {
+    let first_line_end = content.find('\n')?;
+    let format = match content[..first_line_end].trim_end_matches('\r') {
+        "---" => FrontmatterFormat::Yaml,
+        "+++" => FrontmatterFormat::Toml,
+        _ => return None,
+    };
+
+    let inner_start = first_line_end + 1;
+    let mut offset = inner_start;
+    for line in content[inner_start..].split_inclusive('\n') {
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        let closes = trimmed == format.fence()
+            || (format == FrontmatterFormat::Yaml && trimmed == "...");
+        if closes {
+            return Some(FrontmatterBlock {
+                format,
+                inner: inner_start..offset,
+                body_start: offset + line.len(),
+            });
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Split a file into its parsed frontmatter and the remaining markdown body.
+/// Files without a frontmatter block are returned untouched.
+pub fn split(content: &str) -> (Option<Frontmatter>, &str) {
+    let Some(block) = locate(content) else {
+        return (None, content);
+    };
+
+    let raw = &content[block.inner.clone()];
+    let body = &content[block.body_start..];
+
+    match parse_map(raw, block.format) {
+        Ok(map) => (Some(from_map(map)), body),
+        Err(e) => {
+            log::warn!("Ignoring invalid frontmatter: {}", e);
+            (None, body)
+        }
+    }
+}
+
+fn parse_map(raw: &str, format: FrontmatterFormat) -> Result<Map<String, Value>, String> {
+    if raw.trim().is_empty() {
+        return Ok(Map::new());
+    }
+
+    let value = match format {
+        FrontmatterFormat::Yaml => serde_yaml::from_str::<Value>(raw)
+            .map_err(|e| format!("Failed to parse YAML frontmatter: {}", e))?,
+        FrontmatterFormat::Toml => raw
+            .parse::<toml::Table>()
+            .map(|table| toml_to_json(toml::Value::Table(table)))
+            .map_err(|e| format!("Failed to parse TOML frontmatter: {}", e))?,
+    };
+
+    match value {
+        Value::Object(map) => Ok(map),
+        Value::Null => Ok(Map::new()),
+        _ => Err("Frontmatter must be a key/value mapping".to_string()),
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect(),
+        ),
+    }
+}
+
+fn from_map(mut map: Map<String, Value>) -> Frontmatter {
+    let assignee = map.remove("assignee").and_then(value_to_string);
+    let labels = map
+        .remove("labels")
+        .or_else(|| map.remove("tags"))
+        .map(value_to_list)
+        .unwrap_or_default();
+    let estimate = map.remove("estimate").and_then(|v| match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    });
+    let due_date = map
+        .remove("due_date")
+        .or_else(|| map.remove("due"))
+        .and_then(value_to_string);
+    let epic = map.remove("epic").and_then(value_to_string);
+    let sprint = map.remove("sprint").and_then(value_to_string);
+    let priority = map.remove("priority").and_then(value_to_string);
+
+    Frontmatter {
+        assignee,
+        labels,
+        estimate,
+        due_date,
+        epic,
+        sprint,
+        priority,
+        extra: map,
+    }
+}
+
+fn value_to_string(value: Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn value_to_list(value: Value) -> Vec<String> {
+    match value {
+        Value::Array(items) => items.into_iter().filter_map(value_to_string).collect(),
+        // Allow the shorthand `labels: ui, backend`
+        Value::String(s) => s
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,336 @@
+use crate::db;
+use crate::parser::{self, Epic, Ticket};
+use crate::workflow::Workflow;
+use crate::writer::{DocumentBatch, MarkdownDocument};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IdIssueKind {
+    /// Several files in the status folders share a file name
+    Duplicate,
+    /// The `# T-xxx:` heading names another ID than the file
+    HeadingMismatch,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IdIssue {
+    pub kind: IdIssueKind,
+    pub id: String,
+    pub files: Vec<String>,
+    pub heading_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenumberOutcome {
+    pub old_id: String,
+    pub new_id: String,
+    pub file_path: String,
+    /// Epics and tickets whose references were rewritten
+    pub updated_files: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Whether `id` is a plain ticket ID such as `T-047`.
+pub fn is_ticket_id(id: &str) -> bool {
+    id.strip_prefix("T-")
+        .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// How long an empty file reserved by `get_next_ticket_id` or
+/// `get_next_epic_id` holds its ID before a sync frees it again.
+const RESERVATION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// File name part for an epic title: `Project Setup!` becomes `Project-Setup`.
+pub fn file_name_slug(title: &str) -> String {
+    let kept: String = title
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || c.is_whitespace() || *c == '-')
+        .collect();
+    let slug = kept.split_whitespace().collect::<Vec<_>>().join("-");
+    if slug.is_empty() {
+        "Untitled".to_string()
+    } else {
+        slug
+    }
+}
+
+fn is_reservation(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.len() == 0)
+}
+
+/// Delete a reserved empty file. Returns `false` if there was nothing to free.
+pub fn release_reservation(path: &Path) -> Result<bool, String> {
+    if !is_reservation(path) {
+        return Ok(false);
+    }
+    fs::remove_file(path).map_err(|e| format!("Failed to release {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+/// Delete reserved empty ticket and epic files older than `RESERVATION_TTL`,
+/// e.g. left behind when the app quit between reserving and saving.
+pub fn sweep_reservations(m2k_path: &Path, workflow: &Workflow) -> Vec<PathBuf> {
+    let stale = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > RESERVATION_TTL)
+    };
+
+    let mut released = Vec::new();
+    let folders = workflow.ticket_folders().map(|f| (f, "T-")).chain([("epics", "EPIC-")]);
+    for (folder, prefix) in folders {
+        let Ok(entries) = fs::read_dir(m2k_path.join(folder)) else {
+            continue;
+        };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let named = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(prefix) && n.ends_with(".md"));
+            if named && stale(&path) && release_reservation(&path).unwrap_or(false) {
+                released.push(path);
+            }
+        }
+    }
+    released
+}
+
+/// ID at the start of the title heading, e.g. `T-047` in `# T-047: Title`.
+fn heading_id(raw: &str) -> Option<String> {
+    let doc = MarkdownDocument::from_string(raw.to_string());
+    let re = Regex::new(r"^(T-\d+)\b").ok()?;
+    re.captures(doc.title_heading()?).map(|caps| caps[1].to_string())
+}
+
+/// Every ticket file in the status folders, keyed by file name ID, in
+/// workflow column order. Reserved empty files are left out.
+fn ticket_files(m2k_path: &Path, workflow: &Workflow) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut files: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for folder in workflow.ticket_folders() {
+        let Ok(entries) = fs::read_dir(m2k_path.join(folder)) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+            .filter(|p| fs::metadata(p).is_ok_and(|m| m.is_file() && m.len() > 0))
+            .collect();
+        paths.sort();
+        for path in paths {
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                files.entry(id.to_string()).or_default().push(path);
+            }
+        }
+    }
+    files
+}
+
+fn display(paths: &[PathBuf]) -> Vec<String> {
+    paths.iter().map(|p| p.to_string_lossy().to_string()).collect()
+}
+
+/// Find ticket IDs used by more than one file and files whose heading
+/// disagrees with their name.
+pub fn lint_ids(project_path: &str) -> Result<Vec<IdIssue>, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let workflow = Workflow::load_or_default(&m2k_path);
+    let mut issues = Vec::new();
+
+    for (id, paths) in ticket_files(&m2k_path, &workflow) {
+        if paths.len() > 1 {
+            issues.push(IdIssue {
+                kind: IdIssueKind::Duplicate,
+                id: id.clone(),
+                files: display(&paths),
+                heading_id: None,
+                message: format!("{} is used by {} files", id, paths.len()),
+            });
+        }
+
+        for path in &paths {
+            let raw = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            match heading_id(&raw) {
+                Some(heading) if heading != id => issues.push(IdIssue {
+                    kind: IdIssueKind::HeadingMismatch,
+                    id: id.clone(),
+                    files: display(std::slice::from_ref(path)),
+                    message: format!("{} has the heading {}", path.display(), heading),
+                    heading_id: Some(heading),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Give a ticket a new ID: rename its file, rewrite its heading, update epic
+/// ticket tables and other tickets' Dependencies, and update the snapshot.
+/// Either everything is applied or the files are restored.
+///
+/// When `old_id` is a duplicate, `file_path` picks the file to renumber. The
+/// other file keeps `old_id`, so only the renumbered ticket's own epic row is
+/// rewritten and Dependencies on `old_id` are left alone.
+pub fn renumber_ticket(
+    project_path: &str,
+    old_id: &str,
+    new_id: &str,
+    file_path: Option<&str>,
+) -> Result<RenumberOutcome, String> {
+    if !is_ticket_id(new_id) {
+        return Err(format!("Invalid ticket ID: {}", new_id));
+    }
+    if old_id == new_id {
+        return Err(format!("{} already has that ID", old_id));
+    }
+
+    let m2k_path = crate::m2k_dir(project_path);
+    let workflow = Workflow::load_or_default(&m2k_path);
+    let files = ticket_files(&m2k_path, &workflow);
+    // Reserved empty files hold their ID too
+    let taken = workflow
+        .ticket_folders()
+        .any(|folder| m2k_path.join(folder).join(format!("{}.md", new_id)).exists());
+    if taken {
+        return Err(format!("{} already exists", new_id));
+    }
+
+    let candidates = files.get(old_id).cloned().unwrap_or_default();
+    let source = match (file_path, candidates.len()) {
+        (_, 0) => return Err(format!("Ticket {} not found", old_id)),
+        (Some(file_path), _) => candidates
+            .iter()
+            .find(|p| Path::new(file_path) == p.as_path())
+            .cloned()
+            .ok_or(format!("{} is not a file of {}", file_path, old_id))?,
+        (None, 1) => candidates[0].clone(),
+        (None, n) => {
+            return Err(format!(
+                "{} is used by {} files; choose the one to renumber",
+                old_id, n
+            ))
+        }
+    };
+    let status = workflow.status_for_path(&source).unwrap_or_default().to_string();
+    let remaining_path = candidates.iter().find(|p| **p != source).cloned();
+    let ticket = parser::parse_ticket_file(&source, &status)
+        .ok_or(format!("Failed to read {}", source.display()))?;
+    let target = source.with_file_name(format!("{}.md", new_id));
+
+    let mut ticket_doc = MarkdownDocument::load(&source)?;
+    ticket_doc.set_title_prefix(new_id)?;
+
+    let mut edits = Vec::new();
+    let mut warnings = Vec::new();
+
+    // Epic ticket tables
+    let epic_files: Vec<PathBuf> = if remaining_path.is_none() {
+        fs::read_dir(m2k_path.join("epics"))
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        parser::find_epic_file(&m2k_path, &ticket.epic).into_iter().collect()
+    };
+    for path in epic_files {
+        let mut doc = MarkdownDocument::load(&path)?;
+        let mut renamed = doc.set_ticket_row_id(old_id, new_id);
+        while remaining_path.is_none() && renamed {
+            renamed = doc.set_ticket_row_id(old_id, new_id);
+        }
+        if doc.is_dirty() {
+            edits.push((path, doc));
+        }
+    }
+
+    // Dependencies of other tickets
+    let id_re = Regex::new(&format!(r"\b{}\b", regex::escape(old_id))).map_err(|e| e.to_string())?;
+    for path in files.values().flatten().filter(|p| **p != source) {
+        let mut doc = MarkdownDocument::load(path)?;
+        let Some(deps) = doc.section("Dependencies").map(str::to_string) else {
+            continue;
+        };
+        if !id_re.is_match(&deps) {
+            continue;
+        }
+        if remaining_path.is_some() {
+            warnings.push(format!(
+                "{} depends on {}, which another file still uses; left unchanged",
+                path.display(),
+                old_id
+            ));
+            continue;
+        }
+        doc.set_section("Dependencies", &id_re.replace_all(&deps, new_id))?;
+        edits.push((path.clone(), doc));
+    }
+
+    // Write everything, then move the ticket file
+    let docs = edits.iter_mut().map(|(_, doc)| doc).chain(std::iter::once(&mut ticket_doc));
+    let batch = DocumentBatch::save(docs)?;
+    if let Err(e) = fs::rename(&source, &target) {
+        batch.rollback();
+        return Err(format!("Failed to rename ticket: {}", e));
+    }
+
+    // Snapshot, from the files as written
+    let renamed = parser::parse_ticket_file(&target, &status);
+    let remaining = remaining_path.as_ref().and_then(|path| {
+        let status = workflow.status_for_path(path).unwrap_or_default();
+        parser::parse_ticket_file(path, status)
+    });
+    let changed_tickets: Vec<Ticket> = edits
+        .iter()
+        .filter_map(|(path, _)| parser::parse_ticket_file(path, workflow.status_for_path(path)?))
+        .collect();
+    let changed_epics: Vec<(Epic, String)> = edits
+        .iter()
+        .filter(|(path, _)| workflow.status_for_path(path).is_none())
+        .filter_map(|(path, _)| Some((parser::parse_epic_file(path)?, path.to_string_lossy().to_string())))
+        .collect();
+
+    let snapshot_result = match &renamed {
+        Some(renamed) => db::apply_renumber(
+            project_path,
+            old_id,
+            renamed,
+            remaining.as_ref(),
+            &changed_tickets,
+            &changed_epics,
+        ),
+        None => Err(format!("Failed to read {}", target.display())),
+    };
+    if let Err(e) = snapshot_result {
+        if let Err(e) = fs::rename(&target, &source) {
+            log::error!("Failed to move {} back: {}", target.display(), e);
+        }
+        batch.rollback();
+        return Err(e);
+    }
+
+    Ok(RenumberOutcome {
+        old_id: old_id.to_string(),
+        new_id: new_id.to_string(),
+        file_path: target.to_string_lossy().to_string(),
+        updated_files: edits.iter().map(|(path, _)| path.to_string_lossy().to_string()).collect(),
+        warnings,
+    })
+}
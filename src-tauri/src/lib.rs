@@ -1,16 +1,29 @@
+mod analytics;
 mod db;
+mod dependency_graph;
+mod epic_tables;
+mod frontmatter;
+mod ids;
+mod lint;
+mod migrations;
 mod parser;
 mod pty;
+mod search;
+mod sprints;
+mod stats;
 mod watcher;
+mod workflow;
 mod claude_session;
 mod claude_logger;
 mod claude_executor;
 mod task_manager;
 mod task_queue;
+mod writer;
 
 use db::Project;
+use dependency_graph::{BlockedTicket, CriticalPath, DependencyGraph, GraphNode, GraphReport};
 use keyring::Entry;
-use parser::{Epic, Ticket};
+use parser::{Epic, Sprint, Ticket};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -20,9 +33,11 @@ use tokio::sync::Mutex;
 
 use claude_session::{ClaudeSession, SessionState};
 use claude_logger::ClaudeLogger;
-use claude_executor::{ClaudeExecutor, TaskRequest};
-use task_manager::Task;
+use claude_executor::{ClaudeExecutor, RetryPolicy, TaskRequest};
+use task_manager::{InterruptedTaskPolicy, Task, TaskAttempt, TaskStatus};
 use task_queue::{TaskQueue, QueueConfig, QueueStats};
+use workflow::{ColumnCategory, Workflow};
+use writer::MarkdownDocument;
 
 
 const KEYRING_SERVICE: &str = "m2k-app";
@@ -38,6 +53,9 @@ pub struct AppConfig {
     pub sidebar_collapsed: bool,
     #[serde(default = "default_editor_mode")]
     pub default_editor_mode: String,
+    /// Claude tasks found running at startup were cut off by the last quit
+    #[serde(default)]
+    pub interrupted_task_policy: InterruptedTaskPolicy,
 }
 
 fn default_theme() -> String {
@@ -55,6 +73,7 @@ impl Default for AppConfig {
             theme: default_theme(),
             sidebar_collapsed: false,
             default_editor_mode: default_editor_mode(),
+            interrupted_task_policy: InterruptedTaskPolicy::default(),
         }
     }
 }
@@ -162,20 +181,26 @@ async fn submit_claude_task(
     workspace_path: Option<String>,
     timeout_secs: Option<u64>,
     priority: Option<i64>,
+    retry: Option<RetryPolicy>,
 ) -> Result<String, String> {
-    let task_id = task_manager::create_task(prompt.clone(), workspace_path.clone(), priority)?;
-
     let request = TaskRequest {
         prompt,
         workspace_path,
         timeout_secs,
+        retry: retry.unwrap_or_default(),
     };
 
     let queue = TASK_QUEUE.lock().await;
-    if let Some(q) = queue.as_ref() {
-        q.submit(task_id.clone(), request).await?;
-    } else {
+    let Some(q) = queue.as_ref() else {
         return Err("Task queue not initialized".to_string());
+    };
+
+    let task_id = task_manager::create_task(&request, priority)?;
+    let priority = priority.unwrap_or(task_manager::DEFAULT_PRIORITY);
+    if let Err(e) = q.submit(task_id.clone(), request, priority).await {
+        // Not left queued, or the next start would pick it up
+        task_manager::update_task_status(&task_id, TaskStatus::Failed, None, Some(&e)).ok();
+        return Err(e);
     }
 
     Ok(task_id)
@@ -190,6 +215,16 @@ async fn cancel_claude_task(task_id: String) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+async fn reprioritize_task(task_id: String, priority: i64) -> Result<(), String> {
+    let queue = TASK_QUEUE.lock().await;
+    if let Some(q) = queue.as_ref() {
+        q.reprioritize(&task_id, priority).await
+    } else {
+        Err("Task queue not initialized".to_string())
+    }
+}
+
 #[tauri::command]
 fn get_claude_task(task_id: String) -> Result<Option<Task>, String> {
     task_manager::get_task(&task_id)
@@ -221,6 +256,28 @@ fn get_task_logs(task_id: String) -> Result<String, String> {
     logger.read_task_log(&task_id)
 }
 
+#[tauri::command]
+fn get_task_attempts(task_id: String) -> Result<Vec<TaskAttempt>, String> {
+    task_manager::get_task_attempts(&task_id)
+}
+
+#[tauri::command]
+fn get_task_attempt_log(task_id: String, attempt: i64) -> Result<String, String> {
+    let attempts = task_manager::get_task_attempts(&task_id)?;
+    let attempt = attempts
+        .iter()
+        .find(|a| a.attempt == attempt)
+        .ok_or(format!("Task {} has no attempt {}", task_id, attempt))?;
+
+    let log_dir = dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("m2k-claude-tasks")
+        .join("logs");
+
+    let logger = ClaudeLogger::new(log_dir)?;
+    logger.read_log_segment(&task_id, attempt.log_start as u64, attempt.log_end.map(|end| end as u64))
+}
+
 #[tauri::command]
 fn cleanup_old_task_logs(days: u64) -> Result<usize, String> {
     let log_dir = dirs::cache_dir()
@@ -243,6 +300,41 @@ fn parse_epics(path: String) -> Result<Vec<Epic>, String> {
     parser::parse_epics(&path)
 }
 
+#[tauri::command]
+fn parse_sprints(path: String) -> Result<Vec<Sprint>, String> {
+    parser::parse_sprints(&path)
+}
+
+#[tauri::command]
+fn get_dependency_graph(path: String) -> Result<GraphReport, String> {
+    Ok(DependencyGraph::load(&path)?.report())
+}
+
+#[tauri::command]
+fn get_blocked_tickets(path: String) -> Result<Vec<BlockedTicket>, String> {
+    Ok(DependencyGraph::load(&path)?.blocked())
+}
+
+#[tauri::command]
+fn get_ready_tickets(path: String) -> Result<Vec<GraphNode>, String> {
+    Ok(DependencyGraph::load(&path)?.ready())
+}
+
+#[tauri::command]
+fn get_critical_path(path: String, epic_id: String) -> Result<CriticalPath, String> {
+    DependencyGraph::load(&path)?.critical_path(&epic_id)
+}
+
+#[tauri::command]
+fn set_dependency_policy(project_path: String, policy: String) -> Result<(), String> {
+    db::set_dependency_policy(&project_path, &policy)
+}
+
+#[tauri::command]
+fn get_dependency_policy(project_path: String) -> Result<String, String> {
+    db::get_dependency_policy(&project_path)
+}
+
 #[tauri::command]
 fn start_watcher(app: AppHandle, path: String) -> Result<(), String> {
     watcher::start_watcher(app, path)
@@ -252,7 +344,15 @@ fn start_watcher(app: AppHandle, path: String) -> Result<(), String> {
 fn save_markdown_file(path: String, content: String) -> Result<(), String> {
     // log the path
     log::info!("Saving markdown file to: {}", path);
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))
+    let path = Path::new(&path);
+    if path.exists() {
+        let mut doc = MarkdownDocument::load(path)?;
+        doc.replace_content(&content);
+        doc.save()?;
+    } else {
+        MarkdownDocument::from_string(content).save_to(path)?;
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -291,20 +391,83 @@ fn read_image_as_base64(path: String) -> Result<String, String> {
     Ok(format!("data:{};base64,{}", mime_type, base64_str))
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ReservedId {
+    number: i64,
+    id: String,
+    /// Empty file holding the ID; saving the document overwrites it
+    file_path: String,
+}
+
+/// Create `path` only if it does not exist yet; `Ok(false)` when it does.
+fn reserve_file(path: &Path) -> Result<bool, String> {
+    match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+        Err(e) => Err(format!("Failed to reserve {}: {}", path.display(), e)),
+    }
+}
+
+/// Allocate the next epic ID and claim it with an empty
+/// `EPIC-XXX-<title>.md`, so the ID stays taken if the editor never saves.
 #[tauri::command]
-fn get_next_epic_id(project_path: String) -> Result<i64, String> {
-    let next = db::get_and_increment_epic_counter(&project_path)?;
-    // Increment stats
-    db::increment_epic_stats(&project_path)?;
-    Ok(next)
+fn get_next_epic_id(project_path: String, title: String) -> Result<ReservedId, String> {
+    let m2k_path = m2k_dir(&project_path);
+    let folder = m2k_path.join("epics");
+    fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+
+    let highest = parser::highest_file_number([&folder], "EPIC-");
+    let mut reserved = PathBuf::new();
+    let number = db::allocate_id(&project_path, db::IdCounter::Epic, highest, |number| {
+        let id = format!("EPIC-{:03}", number);
+        // Another title may already hold the number
+        if parser::find_epic_file(&m2k_path, &id).is_some() {
+            return Ok(false);
+        }
+        let path = folder.join(format!("{}-{}.md", id, ids::file_name_slug(&title)));
+        let claimed = reserve_file(&path)?;
+        reserved = path;
+        Ok(claimed)
+    })?;
+
+    Ok(ReservedId {
+        number,
+        id: format!("EPIC-{:03}", number),
+        file_path: reserved.to_string_lossy().to_string(),
+    })
 }
 
+/// Allocate the next ticket ID and claim it with an empty `T-XXX.md` in the
+/// first workflow column, so the ID stays taken if the editor never saves.
+#[tauri::command]
+fn get_next_ticket_id(project_path: String) -> Result<ReservedId, String> {
+    let m2k_path = m2k_dir(&project_path);
+    let workflow = Workflow::load_or_default(&m2k_path);
+    let folder = m2k_path.join(&workflow.initial_column().folder);
+    fs::create_dir_all(&folder).map_err(|e| format!("Failed to create {}: {}", folder.display(), e))?;
+
+    let highest = parser::highest_file_number(workflow.ticket_folders().map(|f| m2k_path.join(f)), "T-");
+    let mut reserved = PathBuf::new();
+    let number = db::allocate_id(&project_path, db::IdCounter::Ticket, highest, |number| {
+        let path = folder.join(format!("T-{:03}.md", number));
+        let claimed = reserve_file(&path)?;
+        reserved = path;
+        Ok(claimed)
+    })?;
+
+    Ok(ReservedId {
+        number,
+        id: format!("T-{:03}", number),
+        file_path: reserved.to_string_lossy().to_string(),
+    })
+}
+
+/// Give back an ID reserved by `get_next_*_id` when the document was not
+/// saved. Files that have content are left alone.
 #[tauri::command]
-fn get_next_ticket_id(project_path: String) -> Result<i64, String> {
-    let next = db::get_and_increment_ticket_counter(&project_path)?;
-    // New tickets start in backlog
-    db::increment_ticket_stats(&project_path, "backlog")?;
-    Ok(next)
+fn release_reserved_id(file_path: String) -> Result<bool, String> {
+    ids::release_reservation(Path::new(&file_path))
 }
 
 #[tauri::command]
@@ -337,47 +500,141 @@ fn init_project_counters(project_path: String) -> Result<(), String> {
     sync_stats_from_files(project_path)
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct EpicProgress {
+    epic_id: String,
+    total_tickets: i64,
+    done_tickets: i64,
+    total_criteria: i64,
+    completed_criteria: i64,
+    /// Done tickets over total tickets
+    ticket_progress: f64,
+    /// Checked checkbox criteria over all checkbox criteria of the epic's tickets
+    criteria_progress: Option<f64>,
+}
+
+fn compute_epic_progress(epics: &[Epic], tickets: &[Ticket], workflow: &Workflow) -> Vec<EpicProgress> {
+    epics
+        .iter()
+        .map(|epic| {
+            let epic_tickets: Vec<_> = tickets.iter().filter(|t| t.epic == epic.id).collect();
+            let total_tickets = epic_tickets.len() as i64;
+            let done_tickets = epic_tickets.iter().filter(|t| workflow.is_done(&t.status)).count() as i64;
+
+            let checkboxes = epic_tickets
+                .iter()
+                .flat_map(|t| t.criteria.iter())
+                .filter_map(|c| c.checked);
+            let (total_criteria, completed_criteria) =
+                checkboxes.fold((0i64, 0i64), |(total, done), checked| (total + 1, done + checked as i64));
+
+            EpicProgress {
+                epic_id: epic.id.clone(),
+                total_tickets,
+                done_tickets,
+                total_criteria,
+                completed_criteria,
+                ticket_progress: if total_tickets > 0 {
+                    done_tickets as f64 / total_tickets as f64
+                } else {
+                    0.0
+                },
+                criteria_progress: if total_criteria > 0 {
+                    Some(completed_criteria as f64 / total_criteria as f64)
+                } else {
+                    None
+                },
+            }
+        })
+        .collect()
+}
+
 #[tauri::command]
-fn sync_stats_from_files(project_path: String) -> Result<(), String> {
+fn get_epic_progress(project_path: String) -> Result<Vec<EpicProgress>, String> {
     let tickets = parser::parse_tickets(&project_path)?;
     let epics = parser::parse_epics(&project_path)?;
+    let workflow = Workflow::load_or_default(&m2k_dir(&project_path));
+    Ok(compute_epic_progress(&epics, &tickets, &workflow))
+}
+
+#[tauri::command]
+fn get_sprint_progress(project_path: String) -> Result<Vec<sprints::SprintProgress>, String> {
+    let tickets = parser::parse_tickets(&project_path)?;
+    let sprints = parser::parse_sprints(&project_path)?;
+    let workflow = Workflow::load_or_default(&m2k_dir(&project_path));
+    let today = chrono::Local::now().date_naive();
+    Ok(sprints::compute_sprint_progress(&sprints, &tickets, &workflow, today))
+}
 
-    // Count tickets by status
-    let backlog = tickets.iter().filter(|t| t.status == "backlog").count() as i64;
-    let inprogress = tickets.iter().filter(|t| t.status == "in_progress").count() as i64;
-    let done = tickets.iter().filter(|t| t.status == "done").count() as i64;
+#[tauri::command]
+fn create_sprint(
+    project_path: String,
+    title: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    goals: Option<Vec<String>>,
+) -> Result<Sprint, String> {
+    sprints::create_sprint(
+        &project_path,
+        &title,
+        start_date.as_deref(),
+        end_date.as_deref(),
+        &goals.unwrap_or_default(),
+    )
+}
 
-    // Calculate completed epics (all tickets done)
-    let completed = epics.iter().filter(|epic| {
-        let epic_tickets: Vec<_> = tickets.iter()
-            .filter(|t| t.epic == epic.id)
-            .collect();
-        !epic_tickets.is_empty() && epic_tickets.iter().all(|t| t.status == "done")
-    }).count() as i64;
-
-    // Update all stats in DB
-    db::with_connection(|conn| {
-        conn.execute(
-            "UPDATE project_settings
-             SET total_epics = ?1, completed_epics = ?2,
-                 total_tickets = ?3, backlog_tickets = ?4,
-                 inprogress_tickets = ?5, done_tickets = ?6,
-                 updated_at = datetime('now')
-             WHERE project_path = ?7",
-            rusqlite::params![
-                epics.len() as i64, completed, tickets.len() as i64,
-                backlog, inprogress, done, &project_path
-            ],
-        )?;
-        Ok(())
-    }).map_err(|e| format!("Failed to sync stats: {}", e))
-}
-
-#[tauri::command]
-fn sync_md_to_db(project_path: String) -> Result<(), String> {
+#[tauri::command]
+fn start_sprint(project_path: String, sprint_id: String) -> Result<Sprint, String> {
+    sprints::start_sprint(&project_path, &sprint_id, chrono::Local::now().date_naive())
+}
+
+#[tauri::command]
+fn close_sprint(
+    project_path: String,
+    sprint_id: String,
+    next_sprint: Option<String>,
+) -> Result<sprints::CloseSprintOutcome, String> {
+    sprints::close_sprint(&project_path, &sprint_id, next_sprint.as_deref())
+}
+
+/// Stats are derived from the snapshot tables, so bringing them up to date
+/// means re-reading the files into the snapshot.
+#[tauri::command]
+fn sync_stats_from_files(project_path: String) -> Result<(), String> {
+    db::sync_md_snapshots(&project_path).map(|_| ())
+}
+
+#[tauri::command]
+fn diagnose_stats_drift(project_path: String) -> Result<stats::StatsDrift, String> {
+    stats::diagnose_drift(&project_path)
+}
+
+#[tauri::command]
+fn sync_md_to_db(project_path: String) -> Result<db::SyncReport, String> {
     db::sync_md_snapshots(&project_path)
 }
 
+#[tauri::command]
+fn filter_tickets(project_path: String, filter: db::TicketFilter) -> Result<Vec<Ticket>, String> {
+    db::filter_tickets(&project_path, &filter)
+}
+
+#[tauri::command]
+fn get_ticket_blockers(project_path: String, ticket_id: String) -> Result<Vec<Ticket>, String> {
+    let workflow = Workflow::load_or_default(&m2k_dir(&project_path));
+    db::get_ticket_blockers(&project_path, &ticket_id, &workflow)
+}
+
+#[tauri::command]
+fn search_project(
+    project_path: String,
+    query: String,
+    filters: Option<search::SearchFilters>,
+) -> Result<Vec<search::SearchHit>, String> {
+    search::search_project(&project_path, &query, &filters.unwrap_or_default())
+}
+
 #[tauri::command]
 fn get_project_settings(project_path: String) -> Result<Option<db::ProjectSettings>, String> {
     db::get_project_settings(&project_path)
@@ -390,24 +647,162 @@ fn update_project_counters(project_path: String, epic_counter: i64, ticket_count
 
 
 
+/// Normalize a project path to its `.m2k` folder. The frontend passes either
+/// the project root or the `.m2k` folder itself.
+pub(crate) fn m2k_dir(project_path: &str) -> PathBuf {
+    if project_path.ends_with(".m2k") || project_path.ends_with(".m2k/") {
+        PathBuf::from(project_path)
+    } else {
+        PathBuf::from(project_path).join(".m2k")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct MoveOutcome {
+    warnings: Vec<String>,
+    /// Rule violations that `force` pushed through
+    overrides: Vec<String>,
+}
+
+/// Why a move was refused. Serialized with a `kind` tag so the UI can
+/// explain the rule and offer a forced move.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum MoveError {
+    #[serde(rename_all = "camelCase")]
+    TransitionNotAllowed {
+        message: String,
+        from: String,
+        to: String,
+        allowed: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    WipLimitReached {
+        message: String,
+        status: String,
+        limit: usize,
+        count: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    Blocked {
+        message: String,
+        blockers: Vec<String>,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl MoveError {
+    fn message(&self) -> &str {
+        match self {
+            MoveError::TransitionNotAllowed { message, .. }
+            | MoveError::WipLimitReached { message, .. }
+            | MoveError::Blocked { message, .. }
+            | MoveError::Failed { message } => message,
+        }
+    }
+}
+
+impl From<String> for MoveError {
+    fn from(message: String) -> Self {
+        MoveError::Failed { message }
+    }
+}
+
+fn count_column_tickets(project_dir: &Path, column: &workflow::WorkflowColumn) -> usize {
+    column
+        .folders()
+        .filter_map(|folder| fs::read_dir(project_dir.join(folder)).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "md"))
+        // Empty files are reserved IDs, not tickets
+        .filter(|entry| entry.metadata().is_ok_and(|m| m.is_file() && m.len() > 0))
+        .count()
+}
+
+#[tauri::command]
+fn get_workflow(project_path: String) -> Result<Workflow, String> {
+    Workflow::load(&m2k_dir(&project_path))
+}
+
+#[tauri::command]
+fn get_status_overrides(project_path: String) -> Result<Vec<db::StatusOverride>, String> {
+    db::get_status_overrides(&project_path)
+}
+
+#[tauri::command]
+fn get_ticket_history(project_path: String, ticket_id: String) -> Result<Vec<db::TicketEvent>, String> {
+    db::get_ticket_history(&project_path, &ticket_id)
+}
+
+#[tauri::command]
+fn get_activity_feed(
+    project_path: String,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<db::ActivityPage, String> {
+    db::get_activity_feed(&project_path, limit.unwrap_or(50), offset.unwrap_or(0))
+}
+
+#[tauri::command]
+fn get_flow_analytics(
+    project_path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<analytics::FlowAnalytics, String> {
+    analytics::flow_analytics(&project_path, from.as_deref(), to.as_deref())
+}
+
+#[tauri::command]
+fn get_ticket_timings(
+    project_path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<analytics::TicketTiming>, String> {
+    analytics::project_ticket_timings(&project_path, from.as_deref(), to.as_deref())
+}
+
+#[tauri::command]
+fn get_epic_burn_chart(
+    project_path: String,
+    epic_id: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<analytics::BurnChart, String> {
+    analytics::project_epic_burn_chart(&project_path, &epic_id, from.as_deref(), to.as_deref())
+}
+
+#[tauri::command]
+fn get_sprint_burn_chart(
+    project_path: String,
+    sprint_id: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<analytics::BurnChart, String> {
+    analytics::project_sprint_burn_chart(&project_path, &sprint_id, from.as_deref(), to.as_deref())
+}
+
 #[tauri::command]
 fn move_ticket_to_status(
     project_path: String,
     ticket_id: String,
     new_status: String,
-) -> Result<(), String> {
-    // Normalize path - if it already ends with .m2k, use as-is, otherwise append
-    let project_dir = if project_path.ends_with(".m2k") || project_path.ends_with(".m2k/") {
-        PathBuf::from(&project_path)
-    } else {
-        PathBuf::from(&project_path).join(".m2k")
-    };
+    force: Option<bool>,
+    event_source: Option<db::EventSource>,
+) -> Result<MoveOutcome, MoveError> {
+    let project_dir = m2k_dir(&project_path);
+    let force = force.unwrap_or(false);
+    let mut outcome = MoveOutcome::default();
+    let mut violations: Vec<MoveError> = Vec::new();
+
+    let workflow = Workflow::load(&project_dir)?;
 
     // Find the current ticket file
-    let folders = ["backlog", "inprogress", "done"];
     let mut source_path: Option<PathBuf> = None;
 
-    for folder in &folders {
+    for folder in workflow.ticket_folders() {
         let path = project_dir.join(folder).join(format!("{}.md", ticket_id));
         if path.exists() {
             source_path = Some(path);
@@ -418,88 +813,201 @@ fn move_ticket_to_status(
     let source = source_path.ok_or(format!("Ticket {} not found", ticket_id))?;
 
     // Determine target folder
-    let target_folder = match new_status.as_str() {
-        "backlog" => "backlog",
-        "in_progress" => "inprogress",
-        "done" => "done",
-        _ => return Err(format!("Invalid status: {}", new_status)),
-    };
+    let target_column = workflow
+        .column(&new_status)
+        .ok_or(format!("Invalid status: {}", new_status))?;
+    let target_folder = target_column.folder.as_str();
+    let current_status = workflow.status_for_path(&source).unwrap_or_default().to_string();
+
+    if current_status != new_status {
+        // Transition rules from workflow.toml
+        if !workflow.can_transition(&current_status, &new_status) {
+            violations.push(MoveError::TransitionNotAllowed {
+                message: format!(
+                    "Moving {} from {} to {} is not allowed",
+                    ticket_id, current_status, new_status
+                ),
+                from: current_status.clone(),
+                to: new_status.clone(),
+                allowed: workflow.allowed_targets(&current_status),
+            });
+        }
 
-    let target = project_dir
-        .join(target_folder)
-        .join(format!("{}.md", ticket_id));
+        // WIP limit of the target column
+        if let Some(limit) = target_column.wip_limit {
+            let count = count_column_tickets(&project_dir, target_column);
+            if count >= limit {
+                violations.push(MoveError::WipLimitReached {
+                    message: format!(
+                        "{} already holds {} of {} tickets",
+                        target_column.name, count, limit
+                    ),
+                    status: new_status.clone(),
+                    limit,
+                    count,
+                });
+            }
+        }
+    }
 
-    // Move the file
-    if source != target {
-        fs::rename(&source, &target).map_err(|e| format!("Failed to move ticket: {}", e))?;
+    // Starting work on a ticket whose blockers are unfinished
+    let starts_work = workflow.category(&new_status) == Some(ColumnCategory::Active)
+        && workflow.category(&current_status) != Some(ColumnCategory::Active);
+    if starts_work {
+        let policy = db::get_dependency_policy(&project_path).unwrap_or_else(|_| "warn".to_string());
+        if policy != "off" {
+            let graph = DependencyGraph::load(&project_dir.to_string_lossy())?;
+            let blockers = graph.unmet_blockers(&ticket_id);
+            if !blockers.is_empty() {
+                let message = format!(
+                    "{} is blocked by unfinished tickets: {}",
+                    ticket_id,
+                    blockers.join(", ")
+                );
+                if policy == "block" {
+                    violations.push(MoveError::Blocked { message, blockers });
+                } else {
+                    outcome.warnings.push(message);
+                }
+            }
+        }
     }
 
-    Ok(())
+    if !violations.is_empty() {
+        if !force {
+            return Err(violations.remove(0));
+        }
+
+        outcome.overrides = violations.iter().map(|v| v.message().to_string()).collect();
+    }
+    let override_reason = outcome.overrides.join("; ");
+
+    let target_dir = project_dir.join(target_folder);
+    fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create {}: {}", target_folder, e))?;
+
+    let target = target_dir.join(format!("{}.md", ticket_id));
+
+    // Move the file, recording the change in the ticket history and any
+    // forced move once the rename went through
+    db::change_ticket_status(
+        &project_path,
+        &ticket_id,
+        &new_status,
+        event_source.unwrap_or(db::EventSource::Ui),
+        (!outcome.overrides.is_empty()).then_some(db::ForcedMove {
+            from_status: &current_status,
+            reason: &override_reason,
+        }),
+        || {
+            if source != target {
+                fs::rename(&source, &target).map_err(|e| format!("Failed to move ticket: {}", e))?;
+            }
+            Ok(())
+        },
+    )?;
+
+    if let Err(e) = epic_tables::sync_ticket_file(&project_dir, &target) {
+        outcome.warnings.push(format!("Failed to update the epic table: {}", e));
+    }
+
+    Ok(outcome)
 }
 
 #[tauri::command]
-fn update_epic_ticket_status(
+fn lint_ids(project_path: String) -> Result<Vec<ids::IdIssue>, String> {
+    ids::lint_ids(&project_path)
+}
+
+#[tauri::command]
+fn renumber_ticket(
+    project_path: String,
+    old_id: String,
+    new_id: String,
+    file_path: Option<String>,
+) -> Result<ids::RenumberOutcome, String> {
+    ids::renumber_ticket(&project_path, &old_id, &new_id, file_path.as_deref())
+}
+
+#[tauri::command]
+fn move_ticket_to_epic(
     project_path: String,
-    epic_id: String,
     ticket_id: String,
-    new_status: String,
-) -> Result<(), String> {
-    // Normalize path - if it already ends with .m2k, use as-is, otherwise append
-    let base_dir = if project_path.ends_with(".m2k") || project_path.ends_with(".m2k/") {
-        PathBuf::from(&project_path)
-    } else {
-        PathBuf::from(&project_path).join(".m2k")
-    };
-    let epics_dir = base_dir.join("epics");
+    new_epic_id: String,
+) -> Result<epic_tables::EpicMoveOutcome, String> {
+    epic_tables::move_ticket_to_epic(&project_path, &ticket_id, &new_epic_id)
+}
 
-    // Find the epic file
-    let entries = fs::read_dir(&epics_dir)
-        .map_err(|e| format!("Failed to read epics directory: {}", e))?;
+#[tauri::command]
+fn lint_project(project_path: String) -> Result<lint::LintReport, String> {
+    lint::lint_project(&project_path)
+}
 
-    let mut epic_path: Option<PathBuf> = None;
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            if name.starts_with(&epic_id) && name.ends_with(".md") {
-                epic_path = Some(path);
-                break;
-            }
-        }
-    }
+#[tauri::command]
+fn apply_lint_fixes(project_path: String, fixes: Vec<lint::LintFix>) -> Result<lint::LintReport, String> {
+    lint::apply_lint_fixes(&project_path, &fixes)
+}
 
-    let epic_file = epic_path.ok_or(format!("Epic {} not found", epic_id))?;
-
-    // Read and update the epic content
-    let content = fs::read_to_string(&epic_file)
-        .map_err(|e| format!("Failed to read epic file: {}", e))?;
-
-    // Update the ticket status in the markdown table
-    let updated = content
-        .lines()
-        .map(|line| {
-            if line.contains(&ticket_id) && line.starts_with('|') {
-                // Parse the table row and update status
-                let parts: Vec<&str> = line.split('|').collect();
-                if parts.len() >= 4 {
-                    format!(
-                        "| {} | {} | {} |",
-                        parts[1].trim(),
-                        parts[2].trim(),
-                        new_status
-                    )
-                } else {
-                    line.to_string()
+#[tauri::command]
+fn set_ticket_field(
+    file_path: String,
+    field: String,
+    value: Option<serde_json::Value>,
+) -> Result<bool, String> {
+    let path = Path::new(&file_path);
+    // Tickets sit in `.m2k/<column>/`; anything else has no workflow status
+    let m2k_path = path.parent().and_then(Path::parent);
+
+    let required_text = || match value.as_ref().and_then(|v| v.as_str()).map(str::trim) {
+        Some(text) if !text.is_empty() => Ok(text.to_string()),
+        _ => Err(format!("{} must be a non-empty string", field)),
+    };
+
+    match field.as_str() {
+        "epic" => {
+            // The epic lives in frontmatter, the bold field and both epic
+            // tables; moving keeps them all in step with the database.
+            let epic = required_text()?;
+            let m2k_path = m2k_path.ok_or("Ticket is not inside a project")?;
+            let ticket_id = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .ok_or("Invalid ticket file name")?;
+            let outcome = epic_tables::move_ticket_to_epic(&m2k_path.to_string_lossy(), ticket_id, &epic)?;
+            Ok(!outcome.updated_files.is_empty())
+        }
+        "title" => {
+            let mut doc = MarkdownDocument::load(path)?;
+            doc.set_title(&required_text()?)?;
+            let saved = doc.save()?;
+            if saved {
+                if let Some(m2k_path) = m2k_path {
+                    epic_tables::sync_ticket_file(m2k_path, path)?;
                 }
-            } else {
-                line.to_string()
             }
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+            Ok(saved)
+        }
+        key => {
+            let mut doc = MarkdownDocument::load(path)?;
+            doc.set_frontmatter_field(key, value.as_ref())?;
+            doc.save()
+        }
+    }
+}
 
-    fs::write(&epic_file, updated).map_err(|e| format!("Failed to write epic file: {}", e))?;
+#[tauri::command]
+fn toggle_criterion(file_path: String, index: usize, checked: Option<bool>) -> Result<bool, String> {
+    let mut doc = MarkdownDocument::load(Path::new(&file_path))?;
+    let state = doc.set_checklist_item("Acceptance Criteria", index, checked)?;
+    doc.save()?;
+    Ok(state)
+}
 
-    Ok(())
+#[tauri::command]
+fn set_markdown_section(file_path: String, section: String, content: String) -> Result<bool, String> {
+    let mut doc = MarkdownDocument::load(Path::new(&file_path))?;
+    doc.set_section(&section, &content)?;
+    doc.save()
 }
 
 
@@ -583,8 +1091,24 @@ fn path_exists(path: String) -> bool {
 fn init_m2k_folder(project_path: String) -> Result<String, String> {
     let m2k_path = PathBuf::from(&project_path).join(".m2k");
 
+    // Write the default workflow unless the project already defines one
+    let workflow_config = m2k_path.join(workflow::WORKFLOW_FILE);
+    let workflow = if workflow_config.exists() {
+        Workflow::load(&m2k_path)?
+    } else {
+        fs::create_dir_all(&m2k_path)
+            .map_err(|e| format!("Failed to create .m2k: {}", e))?;
+        fs::write(&workflow_config, workflow::DEFAULT_WORKFLOW_TOML)
+            .map_err(|e| format!("Failed to create {}: {}", workflow::WORKFLOW_FILE, e))?;
+        Workflow::default()
+    };
+
     // Create .m2k folder and subfolders
-    let folders = ["backlog", "inprogress", "done", "epics", "resources"];
+    let folders = workflow
+        .columns
+        .iter()
+        .map(|c| c.folder.as_str())
+        .chain(["epics", "resources", "sprints"]);
 
     for folder in folders {
         let folder_path = m2k_path.join(folder);
@@ -595,20 +1119,39 @@ fn init_m2k_folder(project_path: String) -> Result<String, String> {
     // Create WORKFLOW.md if it doesn't exist
     let workflow_path = m2k_path.join("WORKFLOW.md");
     if !workflow_path.exists() {
-        let workflow_content = r#"# M2K Workflow
+        let column_list: String = workflow
+            .columns
+            .iter()
+            .map(|c| format!("- **{}/**: {}\n", c.folder, c.name))
+            .collect();
+        let lifecycle: String = workflow
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if i == 0 {
+                    format!("1. Create ticket in {}/\n", c.folder)
+                } else {
+                    format!("{}. Move to {}/ ({})\n", i + 1, c.folder, c.name)
+                }
+            })
+            .collect();
+        let workflow_content = format!(
+            r#"# M2K Workflow
 
 ## Folder Structure
-- **backlog/**: Tickets waiting to be worked on
-- **inprogress/**: Tickets currently being worked on
-- **done/**: Completed tickets
-- **epics/**: Epic definitions
+{}- **epics/**: Epic definitions
+- **sprints/**: Sprint definitions (`start`, `end` and `state` frontmatter, `## Goals`)
 - **resources/**: Project resources and documentation
 
 ## Ticket Lifecycle
-1. Create ticket in backlog/
-2. Move to inprogress/ when starting work
-3. Move to done/ when completed
-"#;
+{}
+Columns are defined in `{}`.
+"#,
+            column_list,
+            lifecycle,
+            workflow::WORKFLOW_FILE
+        );
         fs::write(&workflow_path, workflow_content)
             .map_err(|e| format!("Failed to create WORKFLOW.md: {}", e))?;
     }
@@ -810,6 +1353,7 @@ fn sanitize_folder_name(name: &str) -> String {
         .collect()
 }
 
+#[allow(dead_code)]
 fn generate_unique_project_folder(base_path: &Path, project_name: &str) -> PathBuf {
     let sanitized = sanitize_folder_name(project_name);
     let mut candidate = base_path.join(&sanitized);
@@ -1142,11 +1686,6 @@ pub fn run() {
                 log::error!("Failed to initialize database: {}", e);
             }
 
-            // Initialize tasks table
-            if let Err(e) = task_manager::init_tasks_table() {
-                log::error!("Failed to initialize tasks table: {}", e);
-            }
-
             // Initialize task queue
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -1159,9 +1698,17 @@ pub fn run() {
                         let config = QueueConfig {
                             max_concurrent: 5,
                             max_queue_size: 100,
+                            ..Default::default()
                         };
 
                         let queue = TaskQueue::new(config, Arc::new(executor));
+                        let policy = load_config()
+                            .map(|config| config.interrupted_task_policy)
+                            .unwrap_or_default();
+                        match queue.resume(policy).await {
+                            Ok(count) => log::info!("Resumed {} queued task(s)", count),
+                            Err(e) => log::error!("Failed to resume task queue: {}", e),
+                        }
                         queue.start(app_handle.clone()).await;
 
                         let mut global_queue = TASK_QUEUE.lock().await;
@@ -1189,13 +1736,22 @@ pub fn run() {
             claude_logout,
             submit_claude_task,
             cancel_claude_task,
+            reprioritize_task,
             get_claude_task,
             get_all_claude_tasks,
             get_queue_stats,
             get_task_logs,
+            get_task_attempts,
+            get_task_attempt_log,
             cleanup_old_task_logs,
             parse_tickets,
             parse_epics,
+            get_dependency_graph,
+            get_blocked_tickets,
+            get_ready_tickets,
+            get_critical_path,
+            set_dependency_policy,
+            get_dependency_policy,
             start_watcher,
             save_markdown_file,
             read_markdown_file,
@@ -1203,13 +1759,39 @@ pub fn run() {
             read_image_as_base64,
             get_next_epic_id,
             get_next_ticket_id,
+            release_reserved_id,
             init_project_counters,
             sync_stats_from_files,
+            diagnose_stats_drift,
+            get_epic_progress,
             sync_md_to_db,
+            filter_tickets,
+            get_ticket_blockers,
             get_project_settings,
+            search_project,
             update_project_counters,
+            get_workflow,
+            get_status_overrides,
             move_ticket_to_status,
-            update_epic_ticket_status,
+            get_ticket_history,
+            get_activity_feed,
+            get_flow_analytics,
+            get_ticket_timings,
+            get_epic_burn_chart,
+            get_sprint_burn_chart,
+            parse_sprints,
+            get_sprint_progress,
+            create_sprint,
+            start_sprint,
+            close_sprint,
+            lint_ids,
+            renumber_ticket,
+            move_ticket_to_epic,
+            lint_project,
+            apply_lint_fixes,
+            set_ticket_field,
+            set_markdown_section,
+            toggle_criterion,
             spawn_pty,
             write_pty,
             resize_pty,
@@ -0,0 +1,373 @@
+use crate::frontmatter;
+use crate::ids::{self, IdIssueKind};
+use crate::parser::{self, Epic, Ticket};
+use crate::workflow::Workflow;
+use crate::writer::MarkdownDocument;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sections every ticket must have, as the editor validates them.
+const TICKET_SECTIONS: [&str; 2] = ["Description", "Acceptance Criteria"];
+const EPIC_SECTIONS: [&str; 2] = ["Scope", "Tickets"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// An edit that resolves a diagnostic. Fixes are returned with the
+/// diagnostics and sent back as-is to `apply_lint_fixes`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum LintFix {
+    SetEpicRowStatus {
+        epic_file: String,
+        ticket_id: String,
+        status: String,
+    },
+    AddEpicRow {
+        epic_file: String,
+        ticket_id: String,
+        title: String,
+        status: String,
+    },
+    RemoveEpicRow {
+        epic_file: String,
+        ticket_id: String,
+    },
+    AddSection {
+        file: String,
+        section: String,
+        body: String,
+    },
+}
+
+impl LintFix {
+    fn file(&self) -> &str {
+        match self {
+            LintFix::SetEpicRowStatus { epic_file, .. }
+            | LintFix::AddEpicRow { epic_file, .. }
+            | LintFix::RemoveEpicRow { epic_file, .. } => epic_file,
+            LintFix::AddSection { file, .. } => file,
+        }
+    }
+
+    fn apply(&self, doc: &mut MarkdownDocument) -> Result<(), String> {
+        match self {
+            LintFix::SetEpicRowStatus { ticket_id, status, .. } => {
+                if !doc.set_ticket_row_status(ticket_id, status) {
+                    return Err(format!("Epic does not list {}", ticket_id));
+                }
+            }
+            LintFix::AddEpicRow { ticket_id, title, status, .. } => {
+                let table = doc.ticket_table().ok_or("Epic has no ticket table")?;
+                if doc.find_row(ticket_id).is_none() {
+                    doc.append_row(&table, &[ticket_id, title, status]);
+                }
+            }
+            LintFix::RemoveEpicRow { ticket_id, .. } => {
+                if let Some((table, index)) = doc.find_row(ticket_id) {
+                    doc.remove_row(&table.rows[index]);
+                }
+            }
+            LintFix::AddSection { section, body, .. } => {
+                if doc.find_section(section).is_none() {
+                    doc.set_section(section, body)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub file: String,
+    /// 1-based
+    pub line: usize,
+    pub severity: Severity,
+    /// Stable rule ID, e.g. `unknown-epic`
+    pub rule: String,
+    pub message: String,
+    pub fix: Option<LintFix>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub diagnostics: Vec<Diagnostic>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub fixable: usize,
+}
+
+impl LintReport {
+    fn new(mut diagnostics: Vec<Diagnostic>) -> Self {
+        diagnostics.sort_by(|a, b| (&a.file, a.line, a.severity).cmp(&(&b.file, b.line, b.severity)));
+        let count = |severity| diagnostics.iter().filter(|d| d.severity == severity).count();
+        Self {
+            errors: count(Severity::Error),
+            warnings: count(Severity::Warning),
+            fixable: diagnostics.iter().filter(|d| d.fix.is_some()).count(),
+            diagnostics,
+        }
+    }
+}
+
+struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    fn push(&mut self, file: &Path, line: usize, severity: Severity, rule: &str, message: String, fix: Option<LintFix>) {
+        self.0.push(Diagnostic {
+            file: file.to_string_lossy().to_string(),
+            line,
+            severity,
+            rule: rule.to_string(),
+            message,
+            fix,
+        });
+    }
+}
+
+fn lint_title(out: &mut Diagnostics, path: &Path, doc: &MarkdownDocument, kind: &str) {
+    let title = doc
+        .title_heading()
+        .map(|heading| heading.split_once(':').map_or(heading, |(_, title)| title).trim());
+    match title {
+        None => out.push(path, 1, Severity::Error, "missing-title", format!("{} has no title heading", kind), None),
+        Some("") => out.push(
+            path,
+            doc.title_line(),
+            Severity::Error,
+            "empty-title",
+            format!("{} title is empty", kind),
+            None,
+        ),
+        Some(_) => {}
+    }
+}
+
+fn lint_sections(out: &mut Diagnostics, path: &Path, doc: &MarkdownDocument, required: &[&str]) {
+    for section in required {
+        if doc.find_section(section).is_none() {
+            out.push(
+                path,
+                doc.title_line(),
+                Severity::Warning,
+                "missing-section",
+                format!("Missing ## {} section", section),
+                Some(LintFix::AddSection {
+                    file: path.to_string_lossy().to_string(),
+                    section: section.to_string(),
+                    body: String::new(),
+                }),
+            );
+        }
+    }
+}
+
+fn lint_ticket(
+    out: &mut Diagnostics,
+    ticket: &Ticket,
+    epic_files: &BTreeMap<String, PathBuf>,
+    listed: &BTreeMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let path = Path::new(&ticket.file_path);
+    let doc = MarkdownDocument::load(path)?;
+    lint_title(out, path, &doc, "Ticket");
+    lint_sections(out, path, &doc, &TICKET_SECTIONS);
+
+    let epic_line = doc.bold_field_line("Epic").unwrap_or_else(|| doc.title_line());
+    if ticket.epic.is_empty() {
+        out.push(
+            path,
+            epic_line,
+            Severity::Warning,
+            "missing-epic",
+            format!("{} does not belong to an epic", ticket.id),
+            None,
+        );
+    } else if let Some(epic_file) = epic_files.get(&ticket.epic) {
+        // Files not named after a plain ID are reported as `id-mismatch`
+        let in_table = listed.get(&ticket.epic).is_some_and(|ids| ids.contains(&ticket.id));
+        if !in_table && ids::is_ticket_id(&ticket.id) {
+            out.push(
+                path,
+                epic_line,
+                Severity::Warning,
+                "ticket-not-in-epic",
+                format!("{} is not listed in the ticket table of {}", ticket.id, ticket.epic),
+                Some(LintFix::AddEpicRow {
+                    epic_file: epic_file.to_string_lossy().to_string(),
+                    ticket_id: ticket.id.clone(),
+                    title: ticket.title.clone(),
+                    status: ticket.status.clone(),
+                }),
+            );
+        }
+    } else {
+        out.push(
+            path,
+            epic_line,
+            Severity::Error,
+            "unknown-epic",
+            format!("{} references {}, which does not exist", ticket.id, ticket.epic),
+            None,
+        );
+    }
+    Ok(())
+}
+
+fn lint_epic(
+    out: &mut Diagnostics,
+    epic: &Epic,
+    path: &Path,
+    tickets: &BTreeMap<&str, &Ticket>,
+    workflow: &Workflow,
+) -> Result<(), String> {
+    let doc = MarkdownDocument::load(path)?;
+    lint_title(out, path, &doc, "Epic");
+    lint_sections(out, path, &doc, &EPIC_SECTIONS);
+
+    let raw = doc.content();
+    let has_priority = frontmatter::split(raw).0.and_then(|meta| meta.priority).is_some()
+        || doc.find_section("Priority").is_some();
+    if !has_priority {
+        out.push(
+            path,
+            doc.title_line(),
+            Severity::Info,
+            "missing-priority",
+            format!("{} has no priority and counts as {}", epic.id, epic.priority),
+            Some(LintFix::AddSection {
+                file: path.to_string_lossy().to_string(),
+                section: "Priority".to_string(),
+                body: epic.priority.clone(),
+            }),
+        );
+    }
+
+    let Some(table) = doc.ticket_table() else {
+        return Ok(());
+    };
+    let status_column = doc.column_index(&table, "Status").unwrap_or(2);
+    let epic_file = path.to_string_lossy().to_string();
+    for row in &table.rows {
+        let Some(id) = row.cells.first().map(|cell| doc.cell_text(cell)) else {
+            continue;
+        };
+        let line = doc.line_number(row.line.start);
+        let Some(ticket) = tickets.get(id) else {
+            out.push(
+                path,
+                line,
+                Severity::Warning,
+                "unknown-ticket",
+                format!("{} lists {}, which does not exist", epic.id, id),
+                Some(LintFix::RemoveEpicRow {
+                    epic_file: epic_file.clone(),
+                    ticket_id: id.to_string(),
+                }),
+            );
+            continue;
+        };
+
+        let listed_status = row.cells.get(status_column).map(|cell| doc.cell_text(cell));
+        let matches = listed_status.and_then(|s| workflow.status_for_label(s)) == Some(ticket.status.as_str());
+        if !matches {
+            out.push(
+                path,
+                line,
+                Severity::Warning,
+                "epic-status-mismatch",
+                format!(
+                    "{} is listed as {} but is in {}",
+                    id,
+                    listed_status.filter(|s| !s.is_empty()).unwrap_or("no status"),
+                    ticket.status
+                ),
+                Some(LintFix::SetEpicRowStatus {
+                    epic_file: epic_file.clone(),
+                    ticket_id: id.to_string(),
+                    status: ticket.status.clone(),
+                }),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check tickets and epics for problems the parser papers over with defaults.
+pub fn lint_project(project_path: &str) -> Result<LintReport, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let m2k_str = m2k_path.to_string_lossy().to_string();
+    let workflow = Workflow::load_or_default(&m2k_path);
+    let tickets = parser::parse_tickets(&m2k_str)?;
+    let epics = parser::parse_epics(&m2k_str)?;
+    let mut out = Diagnostics(Vec::new());
+
+    let mut epic_files = BTreeMap::new();
+    for epic in &epics {
+        if let Some(path) = parser::find_epic_file(&m2k_path, &epic.id) {
+            epic_files.insert(epic.id.clone(), path);
+        }
+    }
+    let listed: BTreeMap<String, Vec<String>> = epics.iter().map(|e| (e.id.clone(), e.tickets.clone())).collect();
+    let by_id: BTreeMap<&str, &Ticket> = tickets.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    for ticket in &tickets {
+        lint_ticket(&mut out, ticket, &epic_files, &listed)?;
+    }
+    for epic in &epics {
+        if let Some(path) = epic_files.get(&epic.id) {
+            lint_epic(&mut out, epic, path, &by_id, &workflow)?;
+        }
+    }
+
+    // Renumbering needs a decision, so ID problems come without a fix
+    for issue in ids::lint_ids(project_path)? {
+        let rule = match issue.kind {
+            IdIssueKind::Duplicate => "duplicate-id",
+            IdIssueKind::HeadingMismatch => "id-mismatch",
+        };
+        for file in &issue.files {
+            out.push(Path::new(file), 1, Severity::Error, rule, issue.message.clone(), None);
+        }
+    }
+
+    Ok(LintReport::new(out.0))
+}
+
+/// Apply fixes returned by `lint_project`, grouped per file so each file is
+/// written once, and lint again.
+pub fn apply_lint_fixes(project_path: &str, fixes: &[LintFix]) -> Result<LintReport, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let mut by_file: BTreeMap<&str, Vec<&LintFix>> = BTreeMap::new();
+    for fix in fixes {
+        let file = fix.file();
+        if !Path::new(file).starts_with(&m2k_path) {
+            return Err(format!("{} is outside the project", file));
+        }
+        by_file.entry(file).or_default().push(fix);
+    }
+
+    for (file, fixes) in by_file {
+        let path = Path::new(file);
+        if !fs::metadata(path).is_ok_and(|m| m.is_file()) {
+            return Err(format!("{} no longer exists", file));
+        }
+        let mut doc = MarkdownDocument::load(path)?;
+        for fix in fixes {
+            fix.apply(&mut doc)?;
+        }
+        doc.save()?;
+    }
+
+    lint_project(project_path)
+}
@@ -0,0 +1,591 @@
+use rusqlite::{Connection, Result as SqliteResult};
+use std::path::Path;
+
+/// A numbered schema change. `up` runs inside a transaction together with the
+/// `user_version` bump, so a failed migration leaves the database untouched.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    up: fn(&Connection) -> SqliteResult<()>,
+}
+
+/// Ordered list of every schema change. Append new entries with the next
+/// version number; never edit one that has shipped.
+///
+/// Databases created before versioning report `user_version` 0 and may
+/// already contain some of these tables and columns, so the early migrations
+/// only create what is missing.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: initial_schema,
+    },
+    Migration {
+        version: 2,
+        name: "ticket_metadata",
+        up: ticket_metadata,
+    },
+    Migration {
+        version: 3,
+        name: "workflow_stats",
+        up: workflow_stats,
+    },
+    Migration {
+        version: 4,
+        name: "project_scoped_snapshots",
+        up: project_scoped_snapshots,
+    },
+    Migration {
+        version: 5,
+        name: "search_index",
+        up: search_index,
+    },
+    Migration {
+        version: 6,
+        name: "ticket_events",
+        up: ticket_events,
+    },
+    Migration {
+        version: 7,
+        name: "sprints",
+        up: sprints,
+    },
+    Migration {
+        version: 8,
+        name: "derived_stats",
+        up: derived_stats,
+    },
+    Migration {
+        version: 9,
+        name: "persistent_task_queue",
+        up: persistent_task_queue,
+    },
+    Migration {
+        version: 10,
+        name: "task_retries",
+        up: task_retries,
+    },
+];
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub fn current_version(conn: &Connection) -> SqliteResult<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring the database at `db_path` up to `latest_version()`. Refuses to touch
+/// a database written by a newer app, and copies the file aside before the
+/// first pending migration runs.
+pub fn run(conn: &mut Connection, db_path: &Path) -> Result<(), String> {
+    let current = current_version(conn)
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(format!(
+            "Database schema version {} is newer than this version of M2K supports ({}). Please update the app.",
+            current, latest
+        ));
+    }
+
+    if current == latest {
+        return Ok(());
+    }
+
+    if !is_empty(conn).map_err(|e| format!("Failed to inspect database: {}", e))? {
+        backup(db_path, current)?;
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        apply(conn, migration).map_err(|e| {
+            format!(
+                "Migration {} ({}) failed: {}",
+                migration.version, migration.name, e
+            )
+        })?;
+        log::info!("Applied migration {} ({})", migration.version, migration.name);
+    }
+
+    Ok(())
+}
+
+fn apply(conn: &mut Connection, migration: &Migration) -> SqliteResult<()> {
+    let tx = conn.transaction()?;
+    (migration.up)(&tx)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+fn is_empty(conn: &Connection) -> SqliteResult<bool> {
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map(|count| count == 0)
+}
+
+/// Copy `projects.db` to `projects.db.v{version}-{timestamp}.bak` next to it.
+fn backup(db_path: &Path, version: i64) -> Result<(), String> {
+    let file_name = db_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("projects.db");
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let backup_path = db_path.with_file_name(format!("{}.v{}-{}.bak", file_name, version, stamp));
+
+    std::fs::copy(db_path, &backup_path)
+        .map_err(|e| format!("Failed to back up database before migrating: {}", e))?;
+    log::info!("Backed up database to {}", backup_path.display());
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> SqliteResult<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+fn add_column(conn: &Connection, table: &str, column: &str, definition: &str) -> SqliteResult<()> {
+    if !has_column(conn, table, column)? {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+fn initial_schema(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_accessed TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_projects_path ON projects(path);
+        CREATE INDEX IF NOT EXISTS idx_projects_name ON projects(name);
+
+        CREATE TABLE IF NOT EXISTS app_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS project_settings (
+            project_path TEXT PRIMARY KEY,
+            epic_counter INTEGER NOT NULL DEFAULT 0,
+            ticket_counter INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS epics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            epic_id TEXT NOT NULL UNIQUE,
+            title TEXT NOT NULL,
+            scope TEXT,
+            file_path TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS tickets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ticket_id TEXT NOT NULL UNIQUE,
+            epic_id TEXT,
+            title TEXT NOT NULL,
+            description TEXT,
+            status TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (epic_id) REFERENCES epics(epic_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS claude_tasks (
+            id TEXT PRIMARY KEY,
+            prompt TEXT NOT NULL,
+            status TEXT NOT NULL,
+            workspace_path TEXT,
+            result TEXT,
+            error TEXT,
+            log_file TEXT,
+            priority INTEGER DEFAULT 5,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            completed_at TEXT
+        );",
+    )?;
+
+    for column in [
+        "total_epics",
+        "completed_epics",
+        "total_tickets",
+        "backlog_tickets",
+        "inprogress_tickets",
+        "done_tickets",
+    ] {
+        add_column(conn, "project_settings", column, "INTEGER NOT NULL DEFAULT 0")?;
+    }
+    add_column(conn, "epics", "priority", "TEXT NOT NULL DEFAULT 'P4'")?;
+
+    Ok(())
+}
+
+fn ticket_metadata(conn: &Connection) -> SqliteResult<()> {
+    for table in ["epics", "tickets"] {
+        add_column(conn, table, "assignee", "TEXT")?;
+        add_column(conn, table, "labels", "TEXT")?;
+        add_column(conn, table, "estimate", "REAL")?;
+        add_column(conn, table, "due_date", "TEXT")?;
+        add_column(conn, table, "extra", "TEXT")?;
+    }
+    for column in ["criteria", "technical_notes", "testing", "sections"] {
+        add_column(conn, "tickets", column, "TEXT")?;
+    }
+    add_column(conn, "project_settings", "total_criteria", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(conn, "project_settings", "completed_criteria", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Blockers listed in ## Dependencies. Tables already scoped by project
+    // keep their shape.
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ticket_dependencies (
+            ticket_id TEXT NOT NULL,
+            depends_on TEXT NOT NULL,
+            PRIMARY KEY (ticket_id, depends_on)
+        );",
+    )
+}
+
+fn workflow_stats(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS project_status_stats (
+            project_path TEXT NOT NULL,
+            status TEXT NOT NULL,
+            ticket_count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (project_path, status)
+        );
+
+        CREATE TABLE IF NOT EXISTS status_overrides (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_path TEXT NOT NULL,
+            ticket_id TEXT NOT NULL,
+            from_status TEXT NOT NULL,
+            to_status TEXT NOT NULL,
+            reason TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );",
+    )
+}
+
+/// Rebuild `tickets`, `epics` and `ticket_dependencies`, which were keyed by
+/// ID alone. Rows are assigned to the registered project whose folder
+/// contains their file; rows no project owns are dropped and come back with
+/// the next sync.
+fn project_scoped_snapshots(conn: &Connection) -> SqliteResult<()> {
+    if !has_column(conn, "tickets", "project_id")? {
+        // Longest registered path that is the file itself or one of its parents
+        const OWNER: &str = "(SELECT p.id FROM projects p
+              WHERE src.file_path = rtrim(p.path, '/')
+                 OR substr(src.file_path, 1, length(rtrim(p.path, '/')) + 1) = rtrim(p.path, '/') || '/'
+              ORDER BY length(p.path) DESC LIMIT 1)";
+
+        conn.execute_batch(&format!(
+            "CREATE TABLE epics_scoped (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                epic_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                priority TEXT NOT NULL DEFAULT 'P4',
+                scope TEXT,
+                file_path TEXT NOT NULL,
+                assignee TEXT,
+                labels TEXT,
+                estimate REAL,
+                due_date TEXT,
+                extra TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+                UNIQUE (project_id, epic_id)
+            );
+            INSERT OR IGNORE INTO epics_scoped
+                (project_id, epic_id, title, priority, scope, file_path,
+                 assignee, labels, estimate, due_date, extra, created_at, updated_at)
+            SELECT {owner}, epic_id, title, priority, scope, file_path,
+                   assignee, labels, estimate, due_date, extra, created_at, updated_at
+            FROM epics src WHERE {owner} IS NOT NULL;
+
+            CREATE TABLE tickets_scoped (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                ticket_id TEXT NOT NULL,
+                epic_id TEXT,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                criteria TEXT,
+                technical_notes TEXT,
+                testing TEXT,
+                sections TEXT,
+                assignee TEXT,
+                labels TEXT,
+                estimate REAL,
+                due_date TEXT,
+                extra TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+                UNIQUE (project_id, ticket_id)
+            );
+            INSERT OR IGNORE INTO tickets_scoped
+                (project_id, ticket_id, epic_id, title, description, status, file_path,
+                 criteria, technical_notes, testing, sections,
+                 assignee, labels, estimate, due_date, extra, created_at, updated_at)
+            SELECT {owner}, ticket_id, epic_id, title, description, status, file_path,
+                   criteria, technical_notes, testing, sections,
+                   assignee, labels, estimate, due_date, extra, created_at, updated_at
+            FROM tickets src WHERE {owner} IS NOT NULL;
+
+            CREATE TABLE ticket_dependencies_scoped (
+                project_id INTEGER NOT NULL,
+                ticket_id TEXT NOT NULL,
+                depends_on TEXT NOT NULL,
+                PRIMARY KEY (project_id, ticket_id, depends_on),
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+            INSERT OR IGNORE INTO ticket_dependencies_scoped (project_id, ticket_id, depends_on)
+            SELECT t.project_id, d.ticket_id, d.depends_on
+            FROM ticket_dependencies d JOIN tickets_scoped t ON t.ticket_id = d.ticket_id;
+
+            DROP TABLE ticket_dependencies;
+            DROP TABLE tickets;
+            DROP TABLE epics;
+            ALTER TABLE epics_scoped RENAME TO epics;
+            ALTER TABLE tickets_scoped RENAME TO tickets;
+            ALTER TABLE ticket_dependencies_scoped RENAME TO ticket_dependencies;",
+            owner = OWNER
+        ))?;
+    }
+
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_tickets_project_epic ON tickets(project_id, epic_id);
+        CREATE INDEX IF NOT EXISTS idx_tickets_project_status ON tickets(project_id, status);
+        CREATE INDEX IF NOT EXISTS idx_ticket_dependencies_depends_on ON ticket_dependencies(project_id, depends_on);",
+    )
+}
+
+/// Full-text index over tickets, epics and text resources. It starts empty
+/// and is filled by the next snapshot sync.
+fn search_index(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            title,
+            body,
+            item_id,
+            kind UNINDEXED,
+            project_id UNINDEXED,
+            file_path UNINDEXED,
+            status UNINDEXED,
+            epic_id UNINDEXED,
+            tokenize = 'porter unicode61'
+        );",
+    )
+}
+
+/// Audit trail of ticket creation, moves, edits and deletions.
+fn ticket_events(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ticket_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            ticket_id TEXT NOT NULL,
+            event_type TEXT NOT NULL,
+            from_value TEXT,
+            to_value TEXT,
+            source TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_ticket_events_ticket ON ticket_events(project_id, ticket_id, id);",
+    )
+}
+
+fn sprints(conn: &Connection) -> SqliteResult<()> {
+    add_column(conn, "tickets", "sprint_id", "TEXT")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sprints (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            sprint_id TEXT NOT NULL,
+            title TEXT NOT NULL,
+            start_date TEXT,
+            end_date TEXT,
+            goals TEXT,
+            state TEXT NOT NULL DEFAULT 'planned',
+            file_path TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+            UNIQUE (project_id, sprint_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_tickets_project_sprint ON tickets(project_id, sprint_id);",
+    )
+}
+
+/// Project statistics are computed from the snapshot tables on read, so the
+/// stored counters that used to drift from them go away. `epic_counter` and
+/// `ticket_counter` stay: they allocate IDs, they are not statistics.
+fn derived_stats(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS project_status_stats;")?;
+    for column in [
+        "total_epics",
+        "completed_epics",
+        "total_tickets",
+        "backlog_tickets",
+        "inprogress_tickets",
+        "done_tickets",
+        "total_criteria",
+        "completed_criteria",
+    ] {
+        if has_column(conn, "project_settings", column)? {
+            conn.execute_batch(&format!("ALTER TABLE project_settings DROP COLUMN {};", column))?;
+        }
+    }
+    Ok(())
+}
+
+/// Everything needed to rebuild the Claude task queue after a restart.
+fn persistent_task_queue(conn: &Connection) -> SqliteResult<()> {
+    add_column(conn, "claude_tasks", "timeout_secs", "INTEGER")?;
+    add_column(conn, "claude_tasks", "started_at", "TEXT")?;
+    add_column(conn, "claude_tasks", "interrupted_at", "TEXT")?;
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_claude_tasks_status ON claude_tasks(status);")
+}
+
+/// Retry policy and attempt count per task, and one row per attempt with the
+/// byte range of its segment in the task log.
+fn task_retries(conn: &Connection) -> SqliteResult<()> {
+    add_column(conn, "claude_tasks", "retry_policy", "TEXT")?;
+    add_column(conn, "claude_tasks", "attempts", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column(conn, "claude_tasks", "next_attempt_at", "TEXT")?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS claude_task_attempts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id TEXT NOT NULL,
+            attempt INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            error_class TEXT,
+            log_start INTEGER NOT NULL,
+            log_end INTEGER,
+            started_at TEXT NOT NULL DEFAULT (datetime('now')),
+            finished_at TEXT,
+            FOREIGN KEY (task_id) REFERENCES claude_tasks(id) ON DELETE CASCADE,
+            UNIQUE (task_id, attempt)
+        );",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db(name: &str) -> (Connection, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("m2k-migrations-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("projects.db");
+        (Connection::open(&path).unwrap(), path)
+    }
+
+    fn table_exists(conn: &Connection, table: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = ?1",
+            [table],
+            |row| row.get::<_, i64>(0),
+        )
+        .unwrap()
+            > 0
+    }
+
+    #[test]
+    fn fresh_database_reaches_latest_version() {
+        let (mut conn, path) = temp_db("fresh");
+        run(&mut conn, &path).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        for table in ["projects", "tickets", "epics", "ticket_dependencies", "ticket_events", "sprints"] {
+            assert!(table_exists(&conn, table), "{} missing", table);
+        }
+
+        // Nothing to back up for an empty database, and a second run is a no-op
+        run(&mut conn, &path).unwrap();
+        let files = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(files, 1);
+    }
+
+    #[test]
+    fn unversioned_database_is_backed_up_and_scoped_by_project() {
+        let (mut conn, path) = temp_db("legacy");
+        initial_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO projects (name, path) VALUES ('app', '/work/app'), ('app2', '/work/app2/');
+             INSERT INTO tickets (ticket_id, title, status, file_path)
+                 VALUES ('T-001', 'In app', 'backlog', '/work/app/.m2k/backlog/T-001.md');
+             INSERT INTO epics (epic_id, title, file_path)
+                 VALUES ('EPIC-001', 'In app2', '/work/app2/.m2k/epics/EPIC-001.md');",
+        )
+        .unwrap();
+
+        run(&mut conn, &path).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+
+        let ticket_project: String = conn
+            .query_row(
+                "SELECT p.name FROM tickets t JOIN projects p ON p.id = t.project_id WHERE t.ticket_id = 'T-001'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(ticket_project, "app");
+        let epic_project: String = conn
+            .query_row(
+                "SELECT p.name FROM epics e JOIN projects p ON p.id = e.project_id WHERE e.epic_id = 'EPIC-001'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(epic_project, "app2");
+
+        let backups = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("projects.db.v0-"))
+            .count();
+        assert_eq!(backups, 1);
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        let (mut conn, path) = temp_db("newer");
+        conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+        let err = run(&mut conn, &path).unwrap_err();
+        assert!(err.contains("newer"), "{}", err);
+    }
+
+    #[test]
+    fn failed_migration_is_rolled_back() {
+        let (mut conn, _path) = temp_db("failed");
+        let broken = Migration {
+            version: 1,
+            name: "broken",
+            up: |conn| conn.execute_batch("CREATE TABLE half_done (id INTEGER); SELECT * FROM missing_table;"),
+        };
+        assert!(apply(&mut conn, &broken).is_err());
+        assert!(!table_exists(&conn, "half_done"));
+        assert_eq!(current_version(&conn).unwrap(), 0);
+    }
+}
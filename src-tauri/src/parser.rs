@@ -1,7 +1,11 @@
+use crate::frontmatter;
+use crate::workflow::Workflow;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,49 +15,147 @@ pub struct Ticket {
     pub title: String,
     pub epic: String,
     pub description: String,
-    pub criteria: Vec<String>,
+    pub criteria: Vec<Criterion>,
+    /// Share of checkbox criteria that are checked, if the ticket has any
+    pub criteria_progress: Option<f64>,
+    pub technical_notes: String,
+    pub dependencies: Vec<String>,
+    pub testing: String,
+    /// `##` sections the template does not define, keyed by heading
+    pub sections: BTreeMap<String, String>,
     pub status: String,
     pub file_path: String,
+    /// Sprint the ticket is planned into, from the `sprint` metadata key
+    pub sprint: Option<String>,
+    pub assignee: Option<String>,
+    pub labels: Vec<String>,
+    pub estimate: Option<f64>,
+    pub due_date: Option<String>,
+    pub extra: Map<String, Value>,
 }
 
+/// An acceptance criterion. `checked` is `None` for plain `- item` lines and
+/// `Some(_)` for `- [ ] item` / `- [x] item` checkboxes.
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Criterion {
+    pub text: String,
+    pub checked: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Epic {
     pub id: String,
     pub title: String,
     pub priority: String,
     pub scope: String,
     pub tickets: Vec<String>,
+    pub assignee: Option<String>,
+    pub labels: Vec<String>,
+    pub estimate: Option<f64>,
+    pub due_date: Option<String>,
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SprintState {
+    #[default]
+    Planned,
+    Active,
+    Closed,
+}
+
+impl SprintState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SprintState::Planned => "planned",
+            SprintState::Active => "active",
+            SprintState::Closed => "closed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "planned" => Some(SprintState::Planned),
+            "active" => Some(SprintState::Active),
+            "closed" => Some(SprintState::Closed),
+            _ => None,
+        }
+    }
+}
+
+/// A time-box defined by a file in `.m2k/sprints/`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Sprint {
+    pub id: String,
+    pub title: String,
+    /// `YYYY-MM-DD`
+    pub start_date: Option<String>,
+    /// `YYYY-MM-DD`, inclusive
+    pub end_date: Option<String>,
+    pub goals: Vec<String>,
+    pub state: SprintState,
+    pub file_path: String,
 }
 
 pub fn parse_tickets(project_path: &str) -> Result<Vec<Ticket>, String> {
     let mut tickets = Vec::new();
-    let folders = ["backlog", "in-progress", "inprogress", "done"];
+    let workflow = Workflow::load_or_default(Path::new(project_path));
 
-    for folder in folders {
-        let folder_path = Path::new(project_path).join(folder);
-        if !folder_path.exists() {
-            continue;
-        }
+    for column in &workflow.columns {
+        for folder in column.folders() {
+            let folder_path = Path::new(project_path).join(folder);
+            if !folder_path.exists() {
+                continue;
+            }
 
-        let folder_tickets = collect_markdown_files(&folder_path, |path| {
-            parse_ticket_file(path, folder)
-        });
-        tickets.extend(folder_tickets);
+            let folder_tickets = collect_markdown_files(&folder_path, |path| {
+                parse_ticket_file(path, &column.status)
+            });
+            tickets.extend(folder_tickets);
+        }
     }
 
     Ok(tickets)
 }
 
-pub fn parse_ticket_file(path: &Path, folder: &str) -> Option<Ticket> {
-    let content = fs::read_to_string(path).ok()?;
+/// Parse a ticket file. `status` comes from the workflow column whose folder
+/// holds the file.
+pub fn parse_ticket_file(path: &Path, status: &str) -> Option<Ticket> {
+    let raw = fs::read_to_string(path).ok()?;
+    // An empty file is an ID reserved by `get_next_ticket_id`, not yet written
+    if raw.trim().is_empty() {
+        return None;
+    }
     let file_name = path.file_stem()?.to_str()?;
+    let (meta, content) = frontmatter::split(&raw);
+    let meta = meta.unwrap_or_default();
 
     let id = file_name.to_string();
-    let title = extract_title(&content).unwrap_or_else(|| id.clone());
-    let epic = extract_epic(&content).unwrap_or_default();
-    let description = extract_description(&content).unwrap_or_default();
-    let criteria = extract_criteria(&content);
-    let status = folder_to_status(folder);
+    let title = extract_title(content).unwrap_or_else(|| id.clone());
+    let epic = meta
+        .epic
+        .or_else(|| extract_epic(content))
+        .unwrap_or_default();
+    let description = extract_description(content).unwrap_or_default();
+    let criteria = extract_criteria(content);
+    let criteria_progress = criteria_progress(&criteria);
+    let mut sections = extract_sections(content);
+    let technical_notes = sections.remove("Technical Notes").unwrap_or_default();
+    let testing = sections.remove("Testing").unwrap_or_default();
+    let dependencies = sections
+        .remove("Dependencies")
+        .map(|deps| extract_ticket_ids(&deps, &id))
+        .unwrap_or_default();
+    let status = status.to_string();
+    // Tickets have no priority field of their own, so keep the key with the
+    // other unrecognised metadata instead of dropping it
+    let mut extra = meta.extra;
+    if let Some(priority) = meta.priority {
+        extra.insert("priority".to_string(), Value::String(priority));
+    }
 
     Some(Ticket {
         id,
@@ -61,8 +163,19 @@ pub fn parse_ticket_file(path: &Path, folder: &str) -> Option<Ticket> {
         epic,
         description,
         criteria,
+        criteria_progress,
+        technical_notes,
+        dependencies,
+        testing,
+        sections,
         status,
         file_path: path.to_string_lossy().to_string(),
+        sprint: meta.sprint,
+        assignee: meta.assignee,
+        labels: meta.labels,
+        estimate: meta.estimate,
+        due_date: meta.due_date,
+        extra,
     })
 }
 
@@ -93,7 +206,7 @@ fn extract_description(content: &str) -> Option<String> {
         .and_then(|caps| caps.get(1).map(|m| m.as_str().trim().to_string()))
 }
 
-fn extract_criteria(content: &str) -> Vec<String> {
+fn extract_criteria(content: &str) -> Vec<Criterion> {
     let re = Regex::new(r"(?s)## Acceptance Criteria\s*\n(.+?)(?:\n##|\z)").ok();
     if let Some(re) = re {
         if let Some(caps) = re.captures(content) {
@@ -102,7 +215,7 @@ fn extract_criteria(content: &str) -> Vec<String> {
                     .as_str()
                     .lines()
                     .filter(|line| line.trim().starts_with('-'))
-                    .map(|line| line.trim().trim_start_matches('-').trim().to_string())
+                    .map(|line| parse_criterion(line.trim().trim_start_matches('-').trim()))
                     .collect();
             }
         }
@@ -110,13 +223,75 @@ fn extract_criteria(content: &str) -> Vec<String> {
     Vec::new()
 }
 
-fn folder_to_status(folder: &str) -> String {
-    match folder {
-        "backlog" => "backlog".to_string(),
-        "in-progress" | "inprogress" => "in_progress".to_string(),
-        "done" => "done".to_string(),
-        _ => "backlog".to_string(),
+fn parse_criterion(item: &str) -> Criterion {
+    let checked = match item.get(..3) {
+        Some("[ ]") => Some(false),
+        Some("[x]") | Some("[X]") => Some(true),
+        _ => None,
+    };
+
+    let text = match checked {
+        Some(_) => item[3..].trim().to_string(),
+        None => item.to_string(),
+    };
+
+    Criterion { text, checked }
+}
+
+pub fn criteria_progress(criteria: &[Criterion]) -> Option<f64> {
+    let total = criteria.iter().filter(|c| c.checked.is_some()).count();
+    if total == 0 {
+        return None;
+    }
+    let done = criteria.iter().filter(|c| c.checked == Some(true)).count();
+    Some(done as f64 / total as f64)
+}
+
+/// Collect every `## Heading` section except the ones with dedicated fields
+/// (`Description` and `Acceptance Criteria`).
+fn extract_sections(content: &str) -> BTreeMap<String, String> {
+    let mut sections = BTreeMap::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+
+    for line in content.lines() {
+        // Deeper headings (### ...) belong to the enclosing section
+        let is_h2 = line.starts_with("## ");
+        if is_h2 || line.starts_with("# ") {
+            if let Some((name, body)) = current.take() {
+                sections.insert(name, body.join("\n").trim().to_string());
+            }
+            let name = line.trim_start_matches('#').trim();
+            if is_h2 && !name.is_empty() {
+                current = Some((name.to_string(), Vec::new()));
+            }
+        } else if let Some((_, body)) = current.as_mut() {
+            body.push(line);
+        }
+    }
+    if let Some((name, body)) = current {
+        sections.insert(name, body.join("\n").trim().to_string());
+    }
+
+    sections.remove("Description");
+    sections.remove("Acceptance Criteria");
+    sections
+}
+
+/// Ticket IDs referenced in free text, in order of appearance.
+pub fn extract_ticket_ids(text: &str, exclude: &str) -> Vec<String> {
+    let re = match Regex::new(r"\bT-\d+\b") {
+        Ok(re) => re,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut ids: Vec<String> = Vec::new();
+    for m in re.find_iter(text) {
+        let id = m.as_str();
+        if id != exclude && !ids.iter().any(|existing| existing == id) {
+            ids.push(id.to_string());
+        }
     }
+    ids
 }
 
 fn collect_markdown_files<T, F>(path: &Path, parser: F) -> Vec<T>
@@ -131,7 +306,7 @@ where
         .filter_map(|e| e.ok())
     {
         let entry_path = entry.path();
-        if entry_path.is_file() && entry_path.extension().map_or(false, |ext| ext == "md") {
+        if entry_path.is_file() && entry_path.extension().is_some_and(|ext| ext == "md") {
             if let Some(item) = parser(entry_path) {
                 results.push(item);
             }
@@ -141,6 +316,23 @@ where
     results
 }
 
+/// Highest number `N` among `.md` files named `<prefix>N...` in `dirs`.
+/// Looks at file names only, so reserved empty files count too.
+pub fn highest_file_number<P: AsRef<Path>>(dirs: impl IntoIterator<Item = P>, prefix: &str) -> i64 {
+    dirs.into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let rest = name.strip_suffix(".md")?.strip_prefix(prefix)?;
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<i64>().ok()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
 pub fn parse_epics(project_path: &str) -> Result<Vec<Epic>, String> {
     let epics_path = Path::new(project_path).join("epics");
 
@@ -154,20 +346,24 @@ pub fn parse_epics(project_path: &str) -> Result<Vec<Epic>, String> {
 }
 
 pub fn parse_epic_file(path: &Path) -> Option<Epic> {
-    let content = fs::read_to_string(path).ok()?;
+    let raw = fs::read_to_string(path).ok()?;
+    // An empty file is an ID reserved by `get_next_epic_id`, not yet written
+    if raw.trim().is_empty() {
+        return None;
+    }
     let file_name = path.file_stem()?.to_str()?;
+    let (meta, content) = frontmatter::split(&raw);
+    let meta = meta.unwrap_or_default();
 
-    // Extract EPIC-XXX from filename like "EPIC-002-Project-Setup.md"
-    let id_re = Regex::new(r"(EPIC-\d+)").ok()?;
-    let id = id_re
-        .captures(file_name)
-        .and_then(|caps| caps.get(1).map(|m| m.as_str().to_string()))
-        .unwrap_or_else(|| file_name.to_string());
+    let id = epic_id_from_file_name(file_name);
 
-    let title = extract_title(&content).unwrap_or_else(|| id.clone());
-    let priority = extract_priority(&content).unwrap_or_else(|| "P4".to_string());
-    let scope = extract_scope(&content).unwrap_or_default();
-    let tickets = extract_ticket_refs(&content);
+    let title = extract_title(content).unwrap_or_else(|| id.clone());
+    let priority = meta
+        .priority
+        .or_else(|| extract_priority(content))
+        .unwrap_or_else(|| "P4".to_string());
+    let scope = extract_scope(content).unwrap_or_default();
+    let tickets = extract_ticket_refs(content);
 
     Some(Epic {
         id,
@@ -175,9 +371,112 @@ pub fn parse_epic_file(path: &Path) -> Option<Epic> {
         priority,
         scope,
         tickets,
+        assignee: meta.assignee,
+        labels: meta.labels,
+        estimate: meta.estimate,
+        due_date: meta.due_date,
+        extra: meta.extra,
+    })
+}
+
+
+/// Extract EPIC-XXX from a file name like "EPIC-002-Project-Setup.md",
+/// falling back to the whole name.
+pub fn epic_id_from_file_name(file_name: &str) -> String {
+    Regex::new(r"(EPIC-\d+)")
+        .ok()
+        .and_then(|re| re.find(file_name).map(|m| m.as_str().to_string()))
+        .unwrap_or_else(|| file_name.to_string())
+}
+
+/// Locate the markdown file for `epic_id` (e.g. `EPIC-002-Project-Setup.md`).
+pub fn find_epic_file(m2k_path: &Path, epic_id: &str) -> Option<PathBuf> {
+    let id_re = Regex::new(r"^(EPIC-\d+)").ok()?;
+    let entries = fs::read_dir(m2k_path.join("epics")).ok()?;
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.extension().is_some_and(|ext| ext == "md")
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| id_re.captures(stem))
+                    .is_some_and(|caps| &caps[1] == epic_id)
+        })
+}
+
+pub fn parse_sprints(project_path: &str) -> Result<Vec<Sprint>, String> {
+    let sprints_path = Path::new(project_path).join("sprints");
+
+    if !sprints_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sprints = collect_markdown_files(&sprints_path, parse_sprint_file);
+    sprints.sort_by(|a, b| {
+        a.start_date
+            .cmp(&b.start_date)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    Ok(sprints)
+}
+
+/// Parse a sprint file. Dates and state come from the frontmatter keys
+/// `start`, `end` and `state`; goals from the `## Goals` list.
+pub fn parse_sprint_file(path: &Path) -> Option<Sprint> {
+    let raw = fs::read_to_string(path).ok()?;
+    let file_name = path.file_stem()?.to_str()?;
+    let (meta, content) = frontmatter::split(&raw);
+    let mut extra = meta.unwrap_or_default().extra;
+
+    let id = sprint_id_from_file_name(file_name);
+    let title = extract_title(content).unwrap_or_else(|| id.clone());
+    let mut take = |keys: [&str; 2]| {
+        keys.iter()
+            .find_map(|key| extra.remove(*key))
+            .and_then(|v| v.as_str().map(|s| s.trim().to_string()))
+            .filter(|s| !s.is_empty())
+    };
+    let start_date = take(["start", "start_date"]);
+    let end_date = take(["end", "end_date"]);
+    let state = take(["state", "status"])
+        .and_then(|s| SprintState::parse(&s))
+        .unwrap_or_default();
+
+    let goals = extract_sections(content)
+        .get("Goals")
+        .map(|section| {
+            section
+                .lines()
+                .filter(|line| line.trim().starts_with('-'))
+                .map(|line| line.trim().trim_start_matches('-').trim().to_string())
+                .filter(|goal| !goal.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Sprint {
+        id,
+        title,
+        start_date,
+        end_date,
+        goals,
+        state,
+        file_path: path.to_string_lossy().to_string(),
     })
 }
 
+/// Extract SPRINT-XXX from a file name like "SPRINT-004-Search.md",
+/// falling back to the whole name.
+pub fn sprint_id_from_file_name(file_name: &str) -> String {
+    Regex::new(r"(SPRINT-\d+)")
+        .ok()
+        .and_then(|re| re.find(file_name).map(|m| m.as_str().to_string()))
+        .unwrap_or_else(|| file_name.to_string())
+}
 
 fn extract_scope(content: &str) -> Option<String> {
     let re = Regex::new(r"(?s)## Scope\s*\n(.+?)(?:\n##|\z)").ok()?;
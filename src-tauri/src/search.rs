@@ -0,0 +1,243 @@
+use crate::db::with_project;
+use crate::parser::{Epic, Ticket};
+use rusqlite::{Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Resource files indexed as text. Anything else under `.m2k/resources` is
+/// skipped.
+const TEXT_EXTENSIONS: [&str; 9] = ["md", "markdown", "txt", "json", "yaml", "yml", "toml", "csv", "html"];
+
+/// Larger resources are left out of the index.
+const MAX_RESOURCE_BYTES: u64 = 1024 * 1024;
+
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFilters {
+    /// Any of "ticket", "epic", "resource"; all kinds when empty
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    pub status: Option<String>,
+    pub epic: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub kind: String,
+    /// Ticket or epic ID; the path relative to `resources/` for resources
+    pub id: String,
+    /// Title with matches wrapped in `<mark>`
+    pub title: String,
+    /// Excerpt of the body around the best match, wrapped in `<mark>`
+    pub snippet: String,
+    pub file_path: String,
+    pub status: Option<String>,
+    pub epic_id: Option<String>,
+    /// bm25 score; lower is a better match
+    pub rank: f64,
+}
+
+fn ticket_body(ticket: &Ticket) -> String {
+    let mut parts = vec![ticket.description.clone()];
+    parts.extend(ticket.criteria.iter().map(|c| c.text.clone()));
+    parts.push(ticket.technical_notes.clone());
+    parts.push(ticket.testing.clone());
+    parts.extend(ticket.sections.values().cloned());
+    parts.retain(|p| !p.trim().is_empty());
+    parts.join("\n")
+}
+
+fn remove_entry(conn: &Connection, project_id: i64, kind: &str, item_id: &str) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM search_index WHERE project_id = ?1 AND kind = ?2 AND item_id = ?3",
+        rusqlite::params![project_id, kind, item_id],
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_entry(
+    conn: &Connection,
+    project_id: i64,
+    kind: &str,
+    item_id: &str,
+    title: &str,
+    body: &str,
+    file_path: &str,
+    status: Option<&str>,
+    epic_id: Option<&str>,
+) -> SqliteResult<()> {
+    remove_entry(conn, project_id, kind, item_id)?;
+    conn.execute(
+        "INSERT INTO search_index (title, body, kind, item_id, project_id, file_path, status, epic_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![title, body, kind, item_id, project_id, file_path, status, epic_id],
+    )?;
+    Ok(())
+}
+
+pub fn index_ticket(conn: &Connection, project_id: i64, ticket: &Ticket) -> SqliteResult<()> {
+    insert_entry(
+        conn,
+        project_id,
+        "ticket",
+        &ticket.id,
+        &ticket.title,
+        &ticket_body(ticket),
+        &ticket.file_path,
+        Some(&ticket.status),
+        Some(ticket.epic.as_str()).filter(|e| !e.is_empty()),
+    )
+}
+
+pub fn index_epic(conn: &Connection, project_id: i64, epic: &Epic, file_path: &str) -> SqliteResult<()> {
+    insert_entry(
+        conn,
+        project_id,
+        "epic",
+        &epic.id,
+        &epic.title,
+        &epic.scope,
+        file_path,
+        None,
+        Some(&epic.id),
+    )
+}
+
+pub fn remove_ticket(conn: &Connection, project_id: i64, ticket_id: &str) -> SqliteResult<()> {
+    remove_entry(conn, project_id, "ticket", ticket_id)
+}
+
+pub fn remove_epic(conn: &Connection, project_id: i64, epic_id: &str) -> SqliteResult<()> {
+    remove_entry(conn, project_id, "epic", epic_id)
+}
+
+pub fn clear_project(conn: &Connection, project_id: i64) -> SqliteResult<()> {
+    conn.execute("DELETE FROM search_index WHERE project_id = ?1", [project_id])?;
+    Ok(())
+}
+
+pub fn is_text_resource(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Read a resource for indexing. Returns `None` for binary, oversized or
+/// unreadable files.
+fn read_resource(path: &Path) -> Option<String> {
+    if !is_text_resource(path) || fs::metadata(path).ok()?.len() > MAX_RESOURCE_BYTES {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+fn resource_id(m2k_path: &Path, path: &Path) -> String {
+    path.strip_prefix(m2k_path.join("resources"))
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Index (or drop, if it is gone or not text) a single resource file.
+pub fn index_resource(conn: &Connection, project_id: i64, m2k_path: &Path, path: &Path) -> SqliteResult<()> {
+    let id = resource_id(m2k_path, path);
+    match read_resource(path) {
+        Some(content) => {
+            let title = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            insert_entry(
+                conn,
+                project_id,
+                "resource",
+                &id,
+                &title,
+                &content,
+                &path.to_string_lossy(),
+                None,
+                None,
+            )
+        }
+        None => remove_entry(conn, project_id, "resource", &id),
+    }
+}
+
+pub fn index_resources(conn: &Connection, project_id: i64, m2k_path: &Path) -> SqliteResult<()> {
+    conn.execute(
+        "DELETE FROM search_index WHERE project_id = ?1 AND kind = 'resource'",
+        [project_id],
+    )?;
+
+    for entry in WalkDir::new(m2k_path.join("resources"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        index_resource(conn, project_id, m2k_path, entry.path())?;
+    }
+    Ok(())
+}
+
+/// Turn free text into an FTS5 query: every word becomes a quoted prefix
+/// term, so punctuation such as the dash in `T-047` cannot break the syntax.
+fn to_fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+pub fn search_project(project_path: &str, query: &str, filters: &SearchFilters) -> Result<Vec<SearchHit>, String> {
+    let Some(fts_query) = to_fts_query(query) else {
+        return Ok(Vec::new());
+    };
+    let kinds = serde_json::to_string(&filters.kinds).unwrap_or_else(|_| "[]".to_string());
+    let limit = filters.limit.unwrap_or(DEFAULT_LIMIT) as i64;
+
+    with_project(project_path, |conn, project_id| {
+        let mut stmt = conn.prepare(
+            "SELECT kind, item_id,
+                    highlight(search_index, 0, '<mark>', '</mark>'),
+                    snippet(search_index, 1, '<mark>', '</mark>', '…', 16),
+                    file_path, status, epic_id,
+                    bm25(search_index, 10.0, 1.0, 20.0) AS score
+             FROM search_index
+             WHERE search_index MATCH ?1
+               AND project_id = ?2
+               AND (json_array_length(?3) = 0 OR kind IN (SELECT value FROM json_each(?3)))
+               AND (?4 IS NULL OR status = ?4)
+               AND (?5 IS NULL OR epic_id = ?5)
+             ORDER BY score
+             LIMIT ?6",
+        )?;
+
+        let hits = stmt.query_map(
+            rusqlite::params![fts_query, project_id, kinds, filters.status, filters.epic, limit],
+            |row| {
+                Ok(SearchHit {
+                    kind: row.get(0)?,
+                    id: row.get(1)?,
+                    title: row.get(2)?,
+                    snippet: row.get(3)?,
+                    file_path: row.get(4)?,
+                    status: row.get(5)?,
+                    epic_id: row.get(6)?,
+                    rank: row.get(7)?,
+                })
+            },
+        )?;
+
+        hits.collect()
+    })
+}
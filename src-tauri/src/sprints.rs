@@ -0,0 +1,253 @@
+use crate::db::{self, EventSource};
+use crate::parser::{self, Sprint, SprintState, Ticket};
+use crate::workflow::Workflow;
+use crate::writer::MarkdownDocument;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintProgress {
+    pub sprint_id: String,
+    pub state: SprintState,
+    pub total_tickets: i64,
+    pub done_tickets: i64,
+    /// Sum of estimates, counting unestimated tickets as 1
+    pub total_points: f64,
+    pub done_points: f64,
+    pub status_counts: BTreeMap<String, i64>,
+    /// Days left until the end date, negative once it has passed
+    pub days_remaining: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseSprintOutcome {
+    pub sprint_id: String,
+    pub completed: Vec<String>,
+    /// Unfinished tickets moved into `next_sprint`
+    pub rolled_over: Vec<String>,
+    pub next_sprint: Option<String>,
+}
+
+pub fn compute_sprint_progress(
+    sprints: &[Sprint],
+    tickets: &[Ticket],
+    workflow: &Workflow,
+    today: NaiveDate,
+) -> Vec<SprintProgress> {
+    sprints
+        .iter()
+        .map(|sprint| {
+            let members: Vec<&Ticket> = tickets
+                .iter()
+                .filter(|t| t.sprint.as_deref() == Some(sprint.id.as_str()))
+                .collect();
+
+            let mut status_counts: BTreeMap<String, i64> =
+                workflow.statuses().map(|s| (s.to_string(), 0)).collect();
+            let (mut done_tickets, mut total_points, mut done_points) = (0, 0.0, 0.0);
+            for ticket in &members {
+                *status_counts.entry(ticket.status.clone()).or_default() += 1;
+                let points = ticket.estimate.unwrap_or(1.0).max(0.0);
+                total_points += points;
+                if workflow.is_done(&ticket.status) {
+                    done_tickets += 1;
+                    done_points += points;
+                }
+            }
+
+            let days_remaining = sprint
+                .end_date
+                .as_deref()
+                .and_then(|end| NaiveDate::parse_from_str(end, "%Y-%m-%d").ok())
+                .map(|end| (end - today).num_days());
+
+            SprintProgress {
+                sprint_id: sprint.id.clone(),
+                state: sprint.state,
+                total_tickets: members.len() as i64,
+                done_tickets,
+                total_points,
+                done_points,
+                status_counts,
+                days_remaining,
+            }
+        })
+        .collect()
+}
+
+fn find_sprint(sprints: &[Sprint], sprint_id: &str) -> Result<Sprint, String> {
+    sprints
+        .iter()
+        .find(|s| s.id == sprint_id)
+        .cloned()
+        .ok_or(format!("Sprint {} not found", sprint_id))
+}
+
+/// Write `state` back to the sprint file, replacing a `status` key if the
+/// file used that spelling.
+fn set_sprint_state(doc: &mut MarkdownDocument, state: SprintState) -> Result<(), String> {
+    doc.set_frontmatter_field("status", None)?;
+    doc.set_frontmatter_field("state", Some(&Value::String(state.as_str().to_string())))
+}
+
+/// Re-read a sprint file after editing it and refresh its snapshot.
+fn reload_sprint(project_path: &str, file_path: &str) -> Result<Sprint, String> {
+    let sprint = parser::parse_sprint_file(Path::new(file_path))
+        .ok_or(format!("Failed to read sprint file {}", file_path))?;
+    if let Err(e) = db::upsert_sprint(project_path, &sprint) {
+        log::warn!("Failed to update sprint snapshot: {}", e);
+    }
+    Ok(sprint)
+}
+
+/// Create `sprints/SPRINT-XXX.md` with the next free number.
+pub fn create_sprint(
+    project_path: &str,
+    title: &str,
+    start_date: Option<&str>,
+    end_date: Option<&str>,
+    goals: &[String],
+) -> Result<Sprint, String> {
+    for date in start_date.iter().chain(end_date.iter()) {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date: {}", date))?;
+    }
+
+    let m2k_path = crate::m2k_dir(project_path);
+    let sprints_dir = m2k_path.join("sprints");
+    fs::create_dir_all(&sprints_dir).map_err(|e| format!("Failed to create sprints folder: {}", e))?;
+
+    let sprints = parser::parse_sprints(&m2k_path.to_string_lossy())?;
+    let next = sprints
+        .iter()
+        .filter_map(|s| s.id.strip_prefix("SPRINT-")?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let id = format!("SPRINT-{:03}", next);
+
+    let mut content = String::from("---\n");
+    if let Some(start) = start_date {
+        content.push_str(&format!("start: {}\n", start));
+    }
+    if let Some(end) = end_date {
+        content.push_str(&format!("end: {}\n", end));
+    }
+    content.push_str("state: planned\n---\n\n");
+    content.push_str(&format!("# {}: {}\n\n## Goals\n\n", id, title.trim()));
+    for goal in goals.iter().filter(|g| !g.trim().is_empty()) {
+        content.push_str(&format!("- {}\n", goal.trim()));
+    }
+
+    let path = sprints_dir.join(format!("{}.md", id));
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    reload_sprint(project_path, &path.to_string_lossy())
+}
+
+/// Mark a planned sprint active. Only one sprint may be active at a time.
+pub fn start_sprint(project_path: &str, sprint_id: &str, today: NaiveDate) -> Result<Sprint, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let sprints = parser::parse_sprints(&m2k_path.to_string_lossy())?;
+    let sprint = find_sprint(&sprints, sprint_id)?;
+
+    match sprint.state {
+        SprintState::Active => return Ok(sprint),
+        SprintState::Closed => return Err(format!("Sprint {} is already closed", sprint_id)),
+        SprintState::Planned => {}
+    }
+    if let Some(active) = sprints.iter().find(|s| s.state == SprintState::Active) {
+        return Err(format!("Sprint {} is still active; close it first", active.id));
+    }
+
+    let mut doc = MarkdownDocument::load(Path::new(&sprint.file_path))?;
+    set_sprint_state(&mut doc, SprintState::Active)?;
+    if sprint.start_date.is_none() {
+        doc.set_frontmatter_field("start", Some(&Value::String(today.to_string())))?;
+    }
+    doc.save()?;
+
+    reload_sprint(project_path, &sprint.file_path)
+}
+
+/// Close a sprint and move its unfinished tickets into `next_sprint`, or
+/// into the earliest planned sprint after it when none is given.
+pub fn close_sprint(
+    project_path: &str,
+    sprint_id: &str,
+    next_sprint: Option<&str>,
+) -> Result<CloseSprintOutcome, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let m2k_str = m2k_path.to_string_lossy().to_string();
+    let sprints = parser::parse_sprints(&m2k_str)?;
+    let sprint = find_sprint(&sprints, sprint_id)?;
+    if sprint.state == SprintState::Closed {
+        return Err(format!("Sprint {} is already closed", sprint_id));
+    }
+
+    let workflow = Workflow::load_or_default(&m2k_path);
+    let tickets = parser::parse_tickets(&m2k_str)?;
+    let (completed, unfinished): (Vec<&Ticket>, Vec<&Ticket>) = tickets
+        .iter()
+        .filter(|t| t.sprint.as_deref() == Some(sprint_id))
+        .partition(|t| workflow.is_done(&t.status));
+
+    let next = match next_sprint {
+        Some(next_id) => {
+            let next = find_sprint(&sprints, next_id)?;
+            if next.id == sprint.id || next.state == SprintState::Closed {
+                return Err(format!("Cannot roll tickets over into sprint {}", next_id));
+            }
+            Some(next)
+        }
+        None => sprints
+            .iter()
+            .skip_while(|s| s.id != sprint.id)
+            .skip(1)
+            .find(|s| s.state == SprintState::Planned)
+            .cloned(),
+    };
+
+    if !unfinished.is_empty() && next.is_none() {
+        return Err(format!(
+            "No planned sprint to roll {} unfinished tickets into; create the next sprint first",
+            unfinished.len()
+        ));
+    }
+
+    // Tickets first, so a failure leaves the sprint open for another try
+    let mut rolled_over = Vec::new();
+    if let Some(next) = &next {
+        let value = Value::String(next.id.clone());
+        for ticket in &unfinished {
+            let path = Path::new(&ticket.file_path);
+            let mut doc = MarkdownDocument::load(path)?;
+            doc.set_frontmatter_field("sprint", Some(&value))?;
+            doc.save()?;
+
+            // Record the move as ours before the watcher sees the edit
+            if let Some(updated) = parser::parse_ticket_file(path, &ticket.status) {
+                if let Err(e) = db::upsert_ticket(project_path, &updated, EventSource::Ui) {
+                    log::warn!("Failed to update ticket snapshot: {}", e);
+                }
+            }
+            rolled_over.push(ticket.id.clone());
+        }
+    }
+
+    let mut doc = MarkdownDocument::load(Path::new(&sprint.file_path))?;
+    set_sprint_state(&mut doc, SprintState::Closed)?;
+    doc.save()?;
+    reload_sprint(project_path, &sprint.file_path)?;
+
+    Ok(CloseSprintOutcome {
+        sprint_id: sprint.id,
+        completed: completed.iter().map(|t| t.id.clone()).collect(),
+        rolled_over,
+        next_sprint: next.map(|s| s.id),
+    })
+}
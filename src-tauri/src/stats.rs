@@ -0,0 +1,161 @@
+use crate::db::{self, ProjectStats};
+use crate::parser::{self, Epic, Ticket};
+use crate::workflow::Workflow;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A field whose snapshot value differs from the file on disk.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotMismatch {
+    pub id: String,
+    pub field: String,
+    pub file_value: Option<String>,
+    pub snapshot_value: Option<String>,
+}
+
+/// An ID counter that is behind the highest ID on disk, so the next
+/// allocation would reuse an existing ID.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CounterDrift {
+    pub counter: String,
+    pub stored: i64,
+    pub highest_on_disk: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsDrift {
+    pub in_sync: bool,
+    /// Stats computed straight from the markdown files
+    pub files: ProjectStats,
+    /// Stats as `get_project_settings` reports them
+    pub snapshot: ProjectStats,
+    /// Epics and tickets on disk with no snapshot row
+    pub missing_from_snapshot: Vec<String>,
+    /// Snapshot rows whose file is gone
+    pub stale_in_snapshot: Vec<String>,
+    pub mismatches: Vec<SnapshotMismatch>,
+    pub counters: Vec<CounterDrift>,
+}
+
+/// The same numbers `db::get_snapshot_stats` computes in SQL, from parsed files.
+pub fn stats_from_files(epics: &[Epic], tickets: &[Ticket], workflow: &Workflow) -> ProjectStats {
+    let mut stats = ProjectStats::empty(workflow);
+    stats.total_epics = epics.len() as i64;
+    stats.total_tickets = tickets.len() as i64;
+
+    for ticket in tickets {
+        *stats.status_counts.entry(ticket.status.clone()).or_default() += 1;
+        for checked in ticket.criteria.iter().filter_map(|c| c.checked) {
+            stats.total_criteria += 1;
+            stats.completed_criteria += checked as i64;
+        }
+    }
+
+    stats.completed_epics = epics
+        .iter()
+        .filter(|epic| {
+            let mut members = tickets.iter().filter(|t| t.epic == epic.id).peekable();
+            members.peek().is_some() && members.all(|t| workflow.is_done(&t.status))
+        })
+        .count() as i64;
+
+    stats
+}
+
+fn highest_id<'a>(ids: impl Iterator<Item = &'a str>, prefix: &str) -> i64 {
+    ids.filter_map(|id| id.strip_prefix(prefix)?.parse::<i64>().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Compare the files on disk with the snapshot the stats are derived from.
+pub fn diagnose_drift(project_path: &str) -> Result<StatsDrift, String> {
+    let m2k_path = crate::m2k_dir(project_path);
+    let m2k_str = m2k_path.to_string_lossy().to_string();
+    let workflow = Workflow::load_or_default(&m2k_path);
+
+    let epics = parser::parse_epics(&m2k_str)?;
+    let tickets = parser::parse_tickets(&m2k_str)?;
+    let snapshot_epics = db::get_all_epics_snapshot(project_path)?;
+    let snapshot_tickets = db::get_all_tickets_snapshot(project_path)?;
+
+    let mut missing_from_snapshot = Vec::new();
+    let mut stale_in_snapshot = Vec::new();
+    let mut mismatches = Vec::new();
+    let mut compare = |id: &str, field: &str, file: Option<&str>, snapshot: Option<&str>| {
+        if file != snapshot {
+            mismatches.push(SnapshotMismatch {
+                id: id.to_string(),
+                field: field.to_string(),
+                file_value: file.map(str::to_string),
+                snapshot_value: snapshot.map(str::to_string),
+            });
+        }
+    };
+
+    let stored_epics: BTreeMap<&str, &Epic> = snapshot_epics.iter().map(|e| (e.id.as_str(), e)).collect();
+    for epic in &epics {
+        match stored_epics.get(epic.id.as_str()) {
+            Some(stored) => compare(&epic.id, "title", Some(&epic.title), Some(&stored.title)),
+            None => missing_from_snapshot.push(epic.id.clone()),
+        }
+    }
+    let stored_tickets: BTreeMap<&str, &Ticket> =
+        snapshot_tickets.iter().map(|t| (t.id.as_str(), t)).collect();
+    for ticket in &tickets {
+        let Some(stored) = stored_tickets.get(ticket.id.as_str()) else {
+            missing_from_snapshot.push(ticket.id.clone());
+            continue;
+        };
+        compare(&ticket.id, "status", Some(&ticket.status), Some(&stored.status));
+        compare(&ticket.id, "title", Some(&ticket.title), Some(&stored.title));
+        compare(&ticket.id, "epic", Some(&ticket.epic), Some(&stored.epic));
+        compare(&ticket.id, "sprint", ticket.sprint.as_deref(), stored.sprint.as_deref());
+    }
+
+    let on_disk: Vec<&str> = epics
+        .iter()
+        .map(|e| e.id.as_str())
+        .chain(tickets.iter().map(|t| t.id.as_str()))
+        .collect();
+    for id in stored_epics.keys().chain(stored_tickets.keys()) {
+        if !on_disk.contains(id) {
+            stale_in_snapshot.push(id.to_string());
+        }
+    }
+
+    let mut counters = Vec::new();
+    if let Some(settings) = db::get_project_settings(project_path)? {
+        let highest_epic = highest_id(epics.iter().map(|e| e.id.as_str()), "EPIC-");
+        let highest_ticket = highest_id(tickets.iter().map(|t| t.id.as_str()), "T-");
+        for (counter, stored, highest_on_disk) in [
+            ("epic", settings.epic_counter, highest_epic),
+            ("ticket", settings.ticket_counter, highest_ticket),
+        ] {
+            if stored < highest_on_disk {
+                counters.push(CounterDrift { counter: counter.to_string(), stored, highest_on_disk });
+            }
+        }
+    }
+
+    let files = stats_from_files(&epics, &tickets, &workflow);
+    let snapshot = db::get_snapshot_stats(project_path, &workflow)?;
+    let in_sync = files == snapshot
+        && missing_from_snapshot.is_empty()
+        && stale_in_snapshot.is_empty()
+        && mismatches.is_empty()
+        && counters.is_empty();
+
+    Ok(StatsDrift {
+        in_sync,
+        files,
+        snapshot,
+        missing_from_snapshot,
+        stale_in_snapshot,
+        mismatches,
+        counters,
+    })
+}
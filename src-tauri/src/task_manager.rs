@@ -1,6 +1,10 @@
+use crate::claude_executor::{ErrorClass, RetryPolicy, TaskRequest};
 use crate::db::with_connection;
 use serde::{Deserialize, Serialize};
 
+/// Priority of tasks submitted without one. Higher runs first.
+pub const DEFAULT_PRIORITY: i64 = 5;
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskStatus {
@@ -9,6 +13,18 @@ pub enum TaskStatus {
     Completed,
     Failed,
     Timeout,
+    Cancelled,
+}
+
+/// What to do on startup with tasks that were running when the app quit.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InterruptedTaskPolicy {
+    /// Run them again from the start
+    Requeue,
+    /// Leave them failed
+    #[default]
+    Fail,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -17,48 +33,97 @@ pub struct Task {
     pub prompt: String,
     pub status: TaskStatus,
     pub workspace_path: Option<String>,
+    pub timeout_secs: Option<u64>,
     pub result: Option<String>,
     pub error: Option<String>,
     pub log_file: Option<String>,
     pub priority: i64,
     pub created_at: String,
+    pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    /// Set when the app quit while the task was running
+    pub interrupted_at: Option<String>,
+    pub retry_policy: Option<RetryPolicy>,
+    /// Attempts started so far
+    pub attempts: i64,
+    /// When a failed task waiting out its backoff runs again
+    pub next_attempt_at: Option<String>,
 }
 
-pub fn init_tasks_table() -> Result<(), String> {
-    with_connection(|conn| {
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS claude_tasks (
-                id TEXT PRIMARY KEY,
-                prompt TEXT NOT NULL,
-                status TEXT NOT NULL,
-                workspace_path TEXT,
-                result TEXT,
-                error TEXT,
-                log_file TEXT,
-                priority INTEGER DEFAULT 5,
-                created_at TEXT NOT NULL DEFAULT (datetime('now')),
-                completed_at TEXT
-            )",
-            [],
-        )?;
-        Ok(())
+/// One run of a task. Its log entries are the bytes `log_start..log_end` of
+/// the task log.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskAttempt {
+    pub task_id: String,
+    pub attempt: i64,
+    pub status: TaskStatus,
+    pub error: Option<String>,
+    pub error_class: Option<String>,
+    pub log_start: i64,
+    pub log_end: Option<i64>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// A queued task as stored, ready to go back into the queue.
+#[derive(Debug, Clone)]
+pub struct StoredRequest {
+    pub task_id: String,
+    pub request: TaskRequest,
+    pub priority: i64,
+    /// How long the task has been waiting, so it keeps its aging. Negative
+    /// while a retry is still waiting out its backoff.
+    pub waited_secs: i64,
+}
+
+const TASK_COLUMNS: &str = "id, prompt, status, workspace_path, timeout_secs, result, error, log_file, priority,
+     created_at, started_at, completed_at, interrupted_at, retry_policy, attempts, next_attempt_at";
+
+fn status_str(status: &TaskStatus) -> String {
+    format!("{:?}", status).to_lowercase()
+}
+
+fn retry_policy_from_json(json: Option<String>) -> Option<RetryPolicy> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    Ok(Task {
+        id: row.get(0)?,
+        prompt: row.get(1)?,
+        status: parse_status(&row.get::<_, String>(2)?),
+        workspace_path: row.get(3)?,
+        timeout_secs: row.get(4)?,
+        result: row.get(5)?,
+        error: row.get(6)?,
+        log_file: row.get(7)?,
+        priority: row.get(8)?,
+        created_at: row.get(9)?,
+        started_at: row.get(10)?,
+        completed_at: row.get(11)?,
+        interrupted_at: row.get(12)?,
+        retry_policy: retry_policy_from_json(row.get(13)?),
+        attempts: row.get(14)?,
+        next_attempt_at: row.get(15)?,
     })
 }
 
-pub fn create_task(prompt: String, workspace_path: Option<String>, priority: Option<i64>) -> Result<String, String> {
+pub fn create_task(request: &TaskRequest, priority: Option<i64>) -> Result<String, String> {
     let task_id = uuid::Uuid::new_v4().to_string();
+    let retry_policy = serde_json::to_string(&request.retry).map_err(|e| e.to_string())?;
 
     with_connection(|conn| {
         conn.execute(
-            "INSERT INTO claude_tasks (id, prompt, status, workspace_path, priority)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO claude_tasks (id, prompt, status, workspace_path, timeout_secs, priority, retry_policy)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             rusqlite::params![
                 task_id,
-                prompt,
+                request.prompt,
                 "queued",
-                workspace_path,
-                priority.unwrap_or(5),
+                request.workspace_path,
+                request.timeout_secs,
+                priority.unwrap_or(DEFAULT_PRIORITY),
+                retry_policy,
             ],
         )?;
         Ok(())
@@ -73,13 +138,14 @@ pub fn update_task_status(
     result: Option<&str>,
     error: Option<&str>,
 ) -> Result<(), String> {
-    let status_str = format!("{:?}", status).to_lowercase();
+    let status_str = status_str(&status);
 
     with_connection(|conn| {
         conn.execute(
             "UPDATE claude_tasks
              SET status = ?1, result = ?2, error = ?3,
-                 completed_at = CASE WHEN ?1 IN ('completed', 'failed', 'timeout')
+                 started_at = CASE WHEN ?1 = 'running' THEN datetime('now') ELSE started_at END,
+                 completed_at = CASE WHEN ?1 IN ('completed', 'failed', 'timeout', 'cancelled')
                                 THEN datetime('now') ELSE completed_at END
              WHERE id = ?4",
             rusqlite::params![status_str, result, error, task_id],
@@ -88,6 +154,7 @@ pub fn update_task_status(
     })
 }
 
+#[allow(dead_code)]
 pub fn set_task_log_file(task_id: &str, log_file: &str) -> Result<(), String> {
     with_connection(|conn| {
         conn.execute(
@@ -98,27 +165,106 @@ pub fn set_task_log_file(task_id: &str, log_file: &str) -> Result<(), String> {
     })
 }
 
-pub fn get_task(task_id: &str) -> Result<Option<Task>, String> {
+/// Record the start of an attempt whose log segment begins at `log_start`.
+/// Returns the attempt number, counting from 1.
+pub fn start_attempt(task_id: &str, log_start: u64) -> Result<u32, String> {
+    with_connection(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE claude_tasks SET attempts = attempts + 1, next_attempt_at = NULL WHERE id = ?1",
+            [task_id],
+        )?;
+        let attempt: i64 = tx.query_row("SELECT attempts FROM claude_tasks WHERE id = ?1", [task_id], |row| row.get(0))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO claude_task_attempts (task_id, attempt, status, log_start)
+             VALUES (?1, ?2, 'running', ?3)",
+            rusqlite::params![task_id, attempt, log_start as i64],
+        )?;
+        tx.commit()?;
+        Ok(attempt as u32)
+    })
+}
+
+pub fn finish_attempt(
+    task_id: &str,
+    attempt: u32,
+    status: TaskStatus,
+    error: Option<&str>,
+    error_class: Option<ErrorClass>,
+    log_end: u64,
+) -> Result<(), String> {
+    with_connection(|conn| {
+        conn.execute(
+            "UPDATE claude_task_attempts
+             SET status = ?3, error = ?4, error_class = ?5, log_end = ?6, finished_at = datetime('now')
+             WHERE task_id = ?1 AND attempt = ?2",
+            rusqlite::params![
+                task_id,
+                attempt,
+                status_str(&status),
+                error,
+                error_class.map(|class| class.as_str()),
+                log_end as i64,
+            ],
+        )?;
+        Ok(())
+    })
+}
+
+/// Put a failed task back in the queue to run again after `delay_secs`.
+pub fn schedule_retry(task_id: &str, error: &str, delay_secs: u64) -> Result<(), String> {
+    with_connection(|conn| {
+        conn.execute(
+            "UPDATE claude_tasks
+             SET status = 'queued', error = ?2, next_attempt_at = datetime('now', ?3)
+             WHERE id = ?1",
+            rusqlite::params![task_id, error, format!("+{} seconds", delay_secs)],
+        )?;
+        Ok(())
+    })
+}
+
+pub fn get_task_attempts(task_id: &str) -> Result<Vec<TaskAttempt>, String> {
     with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT id, prompt, status, workspace_path, result, error, log_file, priority, created_at, completed_at
-             FROM claude_tasks WHERE id = ?1"
+            "SELECT task_id, attempt, status, error, error_class, log_start, log_end, started_at, finished_at
+             FROM claude_task_attempts
+             WHERE task_id = ?1
+             ORDER BY attempt",
         )?;
 
-        match stmt.query_row([task_id], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                prompt: row.get(1)?,
+        let attempts = stmt.query_map([task_id], |row| {
+            Ok(TaskAttempt {
+                task_id: row.get(0)?,
+                attempt: row.get(1)?,
                 status: parse_status(&row.get::<_, String>(2)?),
-                workspace_path: row.get(3)?,
-                result: row.get(4)?,
-                error: row.get(5)?,
-                log_file: row.get(6)?,
-                priority: row.get(7)?,
-                created_at: row.get(8)?,
-                completed_at: row.get(9)?,
+                error: row.get(3)?,
+                error_class: row.get(4)?,
+                log_start: row.get(5)?,
+                log_end: row.get(6)?,
+                started_at: row.get(7)?,
+                finished_at: row.get(8)?,
             })
-        }) {
+        })?;
+        attempts.collect()
+    })
+}
+
+pub fn set_task_priority(task_id: &str, priority: i64) -> Result<(), String> {
+    with_connection(|conn| {
+        conn.execute(
+            "UPDATE claude_tasks SET priority = ?1 WHERE id = ?2",
+            rusqlite::params![priority, task_id],
+        )?;
+        Ok(())
+    })
+}
+
+pub fn get_task(task_id: &str) -> Result<Option<Task>, String> {
+    with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM claude_tasks WHERE id = ?1", TASK_COLUMNS))?;
+
+        match stmt.query_row([task_id], task_from_row) {
             Ok(task) => Ok(Some(task)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e),
@@ -127,29 +273,93 @@ pub fn get_task(task_id: &str) -> Result<Option<Task>, String> {
 }
 
 pub fn get_all_tasks() -> Result<Vec<Task>, String> {
+    with_connection(|conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM claude_tasks ORDER BY created_at DESC",
+            TASK_COLUMNS
+        ))?;
+
+        let tasks = stmt.query_map([], task_from_row)?;
+        tasks.collect()
+    })
+}
+
+/// Deal with tasks still marked running, which can only be left over from a
+/// run of the app that quit before they finished. The interrupted run counts
+/// as an attempt, so a requeued task that has used up its retry policy's
+/// `max_attempts` fails instead of running again. Returns their IDs.
+pub fn recover_interrupted_tasks(policy: InterruptedTaskPolicy) -> Result<Vec<String>, String> {
+    with_connection(|conn| {
+        let tx = conn.unchecked_transaction()?;
+        let running = {
+            let mut stmt = tx.prepare("SELECT id, attempts, retry_policy FROM claude_tasks WHERE status = 'running'")?;
+            let rows = stmt.query_map([], |row| {
+                let policy = retry_policy_from_json(row.get(2)?).unwrap_or_default();
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, policy.max_attempts))
+            })?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+
+        tx.execute(
+            "UPDATE claude_task_attempts SET status = 'failed', error = 'Interrupted', finished_at = datetime('now')
+             WHERE finished_at IS NULL",
+            [],
+        )?;
+        for (id, attempts, max_attempts) in &running {
+            if policy == InterruptedTaskPolicy::Requeue && *attempts < i64::from(*max_attempts) {
+                tx.execute(
+                    "UPDATE claude_tasks
+                     SET status = 'queued', started_at = NULL, interrupted_at = datetime('now')
+                     WHERE id = ?1",
+                    [id],
+                )?;
+            } else {
+                let error = if policy == InterruptedTaskPolicy::Requeue {
+                    format!("Interrupted: the app quit during attempt {} of {}", attempts, max_attempts)
+                } else {
+                    "Interrupted: the app quit while the task was running".to_string()
+                };
+                tx.execute(
+                    "UPDATE claude_tasks
+                     SET status = 'failed', error = ?2,
+                         interrupted_at = datetime('now'), completed_at = datetime('now')
+                     WHERE id = ?1",
+                    rusqlite::params![id, error],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(running.into_iter().map(|(id, _, _)| id).collect())
+    })
+}
+
+/// Queued tasks in submission order.
+pub fn get_queued_requests() -> Result<Vec<StoredRequest>, String> {
     with_connection(|conn| {
         let mut stmt = conn.prepare(
-            "SELECT id, prompt, status, workspace_path, result, error, log_file, priority, created_at, completed_at
+            "SELECT id, prompt, workspace_path, timeout_secs, priority,
+                    CAST(strftime('%s', 'now') - strftime('%s', COALESCE(next_attempt_at, created_at)) AS INTEGER),
+                    retry_policy
              FROM claude_tasks
-             ORDER BY created_at DESC"
+             WHERE status = 'queued'
+             ORDER BY created_at, rowid",
         )?;
 
-        let tasks = stmt.query_map([], |row| {
-            Ok(Task {
-                id: row.get(0)?,
-                prompt: row.get(1)?,
-                status: parse_status(&row.get::<_, String>(2)?),
-                workspace_path: row.get(3)?,
-                result: row.get(4)?,
-                error: row.get(5)?,
-                log_file: row.get(6)?,
-                priority: row.get(7)?,
-                created_at: row.get(8)?,
-                completed_at: row.get(9)?,
+        let rows = stmt.query_map([], |row| {
+            Ok(StoredRequest {
+                task_id: row.get(0)?,
+                request: TaskRequest {
+                    prompt: row.get(1)?,
+                    workspace_path: row.get(2)?,
+                    timeout_secs: row.get(3)?,
+                    retry: retry_policy_from_json(row.get(6)?).unwrap_or_default(),
+                },
+                priority: row.get::<_, Option<i64>>(4)?.unwrap_or(DEFAULT_PRIORITY),
+                waited_secs: row.get(5)?,
             })
         })?;
-
-        tasks.collect()
+        rows.collect()
     })
 }
 
@@ -160,6 +370,7 @@ fn parse_status(s: &str) -> TaskStatus {
         "completed" => TaskStatus::Completed,
         "failed" => TaskStatus::Failed,
         "timeout" => TaskStatus::Timeout,
+        "cancelled" => TaskStatus::Cancelled,
         _ => TaskStatus::Queued,
     }
 }
@@ -1,15 +1,18 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{Mutex, Semaphore, mpsc};
-use tokio::task::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Semaphore, mpsc, watch};
 use tauri::{AppHandle, Emitter};
-use crate::claude_executor::{ClaudeExecutor, TaskRequest};
-use crate::task_manager::{self, TaskStatus};
+use crate::claude_executor::{ClaudeExecutor, ErrorClass, TaskRequest, CANCELLED_ERROR};
+use crate::task_manager::{self, InterruptedTaskPolicy, TaskStatus};
 
 #[derive(Debug, Clone)]
 pub struct QueueConfig {
     pub max_concurrent: usize,
     pub max_queue_size: usize,
+    /// Waiting this long raises a task's effective priority by one, so low
+    /// priority work is not starved by a steady stream of urgent tasks
+    pub aging_interval: Duration,
 }
 
 impl Default for QueueConfig {
@@ -17,16 +20,113 @@ impl Default for QueueConfig {
         Self {
             max_concurrent: 5,
             max_queue_size: 100,
+            aging_interval: Duration::from_secs(60),
         }
     }
 }
 
+struct QueuedTask {
+    task_id: String,
+    request: TaskRequest,
+    priority: i64,
+    /// Submission order, for FIFO among equal priorities
+    seq: u64,
+    /// When the task may run; aging counts from here. In the future while a
+    /// retry waits out its backoff.
+    ready_at: Instant,
+}
+
+/// Pending tasks, taken highest priority first. Ties go to the task that was
+/// submitted first. The queue is small, so a scan per pop is cheap and lets
+/// aging be computed at the moment a task is picked. Tasks whose `ready_at`
+/// has not come yet are skipped, so a retry waiting for its backoff holds no
+/// concurrency slot.
+struct PendingTasks {
+    tasks: Vec<QueuedTask>,
+    next_seq: u64,
+    aging_interval: Duration,
+}
+
+impl PendingTasks {
+    fn new(aging_interval: Duration) -> Self {
+        Self {
+            tasks: Vec::new(),
+            next_seq: 0,
+            aging_interval,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn contains(&self, task_id: &str) -> bool {
+        self.tasks.iter().any(|t| t.task_id == task_id)
+    }
+
+    fn push(&mut self, task_id: String, request: TaskRequest, priority: i64, ready_at: Instant) {
+        self.tasks.push(QueuedTask {
+            task_id,
+            request,
+            priority,
+            seq: self.next_seq,
+            ready_at,
+        });
+        self.next_seq += 1;
+    }
+
+    fn waiting_for_retry(&self) -> usize {
+        let now = Instant::now();
+        self.tasks.iter().filter(|t| t.ready_at > now).count()
+    }
+
+    fn effective_priority(&self, task: &QueuedTask, now: Instant) -> i64 {
+        let waited = now.saturating_duration_since(task.ready_at).as_secs_f64();
+        let interval = self.aging_interval.as_secs_f64();
+        let boost = if interval > 0.0 { (waited / interval) as i64 } else { 0 };
+        task.priority.saturating_add(boost)
+    }
+
+    fn pop(&mut self) -> Option<QueuedTask> {
+        let now = Instant::now();
+        let index = self
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.ready_at <= now)
+            .max_by_key(|(_, t)| (self.effective_priority(t, now), std::cmp::Reverse(t.seq)))
+            .map(|(i, _)| i)?;
+        Some(self.tasks.remove(index))
+    }
+
+    /// Change the priority of a queued task. It keeps its place among equal
+    /// priorities and the age it has built up.
+    fn set_priority(&mut self, task_id: &str, priority: i64) -> bool {
+        match self.tasks.iter_mut().find(|t| t.task_id == task_id) {
+            Some(task) => {
+                task.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&mut self, task_id: &str) -> bool {
+        let before = self.tasks.len();
+        self.tasks.retain(|t| t.task_id != task_id);
+        self.tasks.len() != before
+    }
+}
+
 pub struct TaskQueue {
     config: QueueConfig,
-    pending: Arc<Mutex<VecDeque<(String, TaskRequest)>>>,
-    active: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
+    pending: Arc<Mutex<PendingTasks>>,
+    /// Running tasks, with the switch that stops each. A task removes itself
+    /// once its outcome is recorded.
+    active: Arc<Mutex<HashMap<String, watch::Sender<bool>>>>,
     semaphore: Arc<Semaphore>,
     executor: Arc<ClaudeExecutor>,
+    #[allow(dead_code)]
     shutdown_tx: mpsc::Sender<()>,
     shutdown_rx: Arc<Mutex<mpsc::Receiver<()>>>,
 }
@@ -37,8 +137,8 @@ impl TaskQueue {
 
         Self {
             semaphore: Arc::new(Semaphore::new(config.max_concurrent)),
+            pending: Arc::new(Mutex::new(PendingTasks::new(config.aging_interval))),
             config,
-            pending: Arc::new(Mutex::new(VecDeque::new())),
             active: Arc::new(Mutex::new(HashMap::new())),
             executor,
             shutdown_tx,
@@ -47,17 +147,44 @@ impl TaskQueue {
     }
 
     /// Submit task to queue
-    pub async fn submit(&self, task_id: String, request: TaskRequest) -> Result<(), String> {
+    pub async fn submit(&self, task_id: String, request: TaskRequest, priority: i64) -> Result<(), String> {
         let mut pending = self.pending.lock().await;
 
         if pending.len() >= self.config.max_queue_size {
             return Err("Task queue full".to_string());
         }
 
-        pending.push_back((task_id, request));
+        pending.push(task_id, request, priority, Instant::now());
         Ok(())
     }
 
+    /// Rebuild the queue from `claude_tasks` (call once on app startup, before
+    /// `start`). Tasks left running by a previous run are handled per
+    /// `policy`. Restored tasks were accepted before, so the size limit does
+    /// not apply to them. Returns the number of tasks queued.
+    pub async fn resume(&self, policy: InterruptedTaskPolicy) -> Result<usize, String> {
+        let interrupted = task_manager::recover_interrupted_tasks(policy)?;
+        if !interrupted.is_empty() {
+            log::warn!("{} task(s) were interrupted by the last shutdown ({:?})", interrupted.len(), policy);
+        }
+
+        let stored = task_manager::get_queued_requests()?;
+        let now = Instant::now();
+        let mut pending = self.pending.lock().await;
+        for task in &stored {
+            let offset = Duration::from_secs(task.waited_secs.unsigned_abs());
+            let ready_at = if task.waited_secs >= 0 {
+                now.checked_sub(offset).unwrap_or(now)
+            } else {
+                now + offset
+            };
+            if !pending.contains(&task.task_id) {
+                pending.push(task.task_id.clone(), task.request.clone(), task.priority, ready_at);
+            }
+        }
+        Ok(pending.len())
+    }
+
     /// Start processing queue (call once on app startup)
     pub async fn start(&self, app: AppHandle) {
         let pending = self.pending.clone();
@@ -88,8 +215,8 @@ impl TaskQueue {
     }
 
     async fn process_next_task(
-        pending: Arc<Mutex<VecDeque<(String, TaskRequest)>>>,
-        active: Arc<Mutex<HashMap<String, JoinHandle<()>>>>,
+        pending: Arc<Mutex<PendingTasks>>,
+        active: Arc<Mutex<HashMap<String, watch::Sender<bool>>>>,
         semaphore: Arc<Semaphore>,
         executor: Arc<ClaudeExecutor>,
         app: AppHandle,
@@ -101,31 +228,81 @@ impl TaskQueue {
         }
         let permit = permit.unwrap();
 
-        // Get next task
+        // Take the next task and track it as active under the queue lock, so
+        // `cancel_task` always finds it in one of the two
+        let (cancel_tx, cancel_rx) = watch::channel(false);
         let task = {
             let mut queue = pending.lock().await;
-            queue.pop_front()
+            let task = queue.pop();
+            if let Some(task) = &task {
+                active.lock().await.insert(task.task_id.clone(), cancel_tx);
+            }
+            task
         };
 
-        if let Some((task_id, request)) = task {
+        if let Some(QueuedTask { task_id, request, priority, .. }) = task {
             // Update status to running
             task_manager::update_task_status(&task_id, TaskStatus::Running, None, None).ok();
+            let retry = request.retry.clone();
+            let log_start = executor.log_size(&task_id);
+            let attempt = match task_manager::start_attempt(&task_id, log_start) {
+                Ok(attempt) => attempt,
+                Err(e) => {
+                    log::error!("Failed to record attempt of task {}: {}", task_id, e);
+                    1
+                }
+            };
+            executor.log_attempt_start(&task_id, attempt, retry.max_attempts);
 
             // Spawn task execution
             let executor_clone = executor.clone();
             let app_clone = app.clone();
             let task_id_clone = task_id.clone();
+            let active_clone = active.clone();
+            let pending_clone = pending.clone();
 
-            let handle = tokio::spawn(async move {
+            tokio::spawn(async move {
                 let result = executor_clone.execute_task(
                     &task_id_clone,
-                    request,
-                    app_clone.clone()
+                    request.clone(),
+                    app_clone.clone(),
+                    cancel_rx,
                 ).await;
+                let log_end = executor_clone.log_size(&task_id_clone);
 
-                // Update status on completion
+                // Update status on completion. A cancel that comes in after the
+                // task finished does not change its outcome.
                 match result {
+                    Err(e) if e == CANCELLED_ERROR => {
+                        task_manager::finish_attempt(
+                            &task_id_clone,
+                            attempt,
+                            TaskStatus::Cancelled,
+                            Some(CANCELLED_ERROR),
+                            None,
+                            log_end,
+                        ).ok();
+                        task_manager::update_task_status(
+                            &task_id_clone,
+                            TaskStatus::Cancelled,
+                            None,
+                            Some(CANCELLED_ERROR),
+                        ).ok();
+
+                        app_clone.emit("task-cancelled", serde_json::json!({
+                            "task_id": task_id_clone,
+                            "status": "cancelled"
+                        })).ok();
+                    }
                     Ok(task_result) => {
+                        task_manager::finish_attempt(
+                            &task_id_clone,
+                            attempt,
+                            TaskStatus::Completed,
+                            None,
+                            None,
+                            log_end,
+                        ).ok();
                         task_manager::update_task_status(
                             &task_id_clone,
                             TaskStatus::Completed,
@@ -139,27 +316,61 @@ impl TaskQueue {
                         })).ok();
                     }
                     Err(e) => {
-                        task_manager::update_task_status(
+                        let class = ErrorClass::classify(&e);
+                        task_manager::finish_attempt(
                             &task_id_clone,
+                            attempt,
                             TaskStatus::Failed,
-                            None,
                             Some(&e),
+                            Some(class),
+                            log_end,
                         ).ok();
 
-                        app_clone.emit("task-failed", serde_json::json!({
-                            "task_id": task_id_clone,
-                            "error": e
-                        })).ok();
+                        match retry.next_delay(attempt, class) {
+                            Some(delay) => {
+                                // Back in the queue right away, but not ready until the
+                                // backoff has passed; the slot is released below. It
+                                // moves from active to pending under the queue lock.
+                                task_manager::schedule_retry(&task_id_clone, &e, delay.as_secs()).ok();
+                                let mut queue = pending_clone.lock().await;
+                                active_clone.lock().await.remove(&task_id_clone);
+                                queue.push(
+                                    task_id_clone.clone(),
+                                    request,
+                                    priority,
+                                    Instant::now() + delay,
+                                );
+                                drop(queue);
+
+                                app_clone.emit("task-retrying", serde_json::json!({
+                                    "task_id": task_id_clone,
+                                    "attempt": attempt,
+                                    "error": e,
+                                    "delay_secs": delay.as_secs()
+                                })).ok();
+                            }
+                            None => {
+                                task_manager::update_task_status(
+                                    &task_id_clone,
+                                    TaskStatus::Failed,
+                                    None,
+                                    Some(&e),
+                                ).ok();
+
+                                app_clone.emit("task-failed", serde_json::json!({
+                                    "task_id": task_id_clone,
+                                    "error": e
+                                })).ok();
+                            }
+                        }
                     }
                 }
 
+                active_clone.lock().await.remove(&task_id_clone);
+
                 // Release permit
                 drop(permit);
             });
-
-            // Track active task
-            let mut active_tasks = active.lock().await;
-            active_tasks.insert(task_id, handle);
         } else {
             // No tasks, wait a bit
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -167,27 +378,29 @@ impl TaskQueue {
         }
     }
 
-    /// Cancel a specific task
+    /// Cancel a specific task. A queued task is dropped right away. A running
+    /// task has its process stopped; it is recorded as cancelled once the
+    /// process is gone and its workspace removed.
     pub async fn cancel_task(&self, task_id: &str) -> Result<(), String> {
-        // Remove from pending queue
+        // Remove from pending queue; it would otherwise be resumed on restart
         {
             let mut pending = self.pending.lock().await;
-            pending.retain(|(id, _)| id != task_id);
-        }
-
-        // Abort active task
-        {
-            let mut active = self.active.lock().await;
-            if let Some(handle) = active.remove(task_id) {
-                handle.abort();
-
+            if pending.remove(task_id) {
                 task_manager::update_task_status(
                     task_id,
-                    TaskStatus::Failed,
+                    TaskStatus::Cancelled,
                     None,
-                    Some("Task cancelled by user"),
+                    Some(CANCELLED_ERROR),
                 )?;
+                return Ok(());
+            }
+        }
 
+        // Stop active task
+        {
+            let active = self.active.lock().await;
+            if let Some(cancel) = active.get(task_id) {
+                cancel.send_replace(true);
                 return Ok(());
             }
         }
@@ -195,6 +408,19 @@ impl TaskQueue {
         Err(format!("Task {} not found", task_id))
     }
 
+    /// Change the priority of a task that is still waiting to run, in the
+    /// queue and in `claude_tasks`.
+    pub async fn reprioritize(&self, task_id: &str, priority: i64) -> Result<(), String> {
+        let mut pending = self.pending.lock().await;
+        if !pending.contains(task_id) {
+            return Err(format!("Task {} is not queued", task_id));
+        }
+
+        task_manager::set_task_priority(task_id, priority)?;
+        pending.set_priority(task_id, priority);
+        Ok(())
+    }
+
     /// Get queue statistics
     pub async fn get_stats(&self) -> QueueStats {
         let pending = self.pending.lock().await;
@@ -202,6 +428,7 @@ impl TaskQueue {
 
         QueueStats {
             pending_count: pending.len(),
+            waiting_retry_count: pending.waiting_for_retry(),
             active_count: active.len(),
             available_slots: self.semaphore.available_permits(),
             max_concurrent: self.config.max_concurrent,
@@ -209,6 +436,7 @@ impl TaskQueue {
     }
 
     /// Shutdown queue gracefully
+    #[allow(dead_code)]
     pub async fn shutdown(&self) {
         self.shutdown_tx.send(()).await.ok();
 
@@ -219,6 +447,7 @@ impl TaskQueue {
         ).await.ok();
     }
 
+    #[allow(dead_code)]
     async fn wait_for_completion(&self) {
         loop {
             let active = self.active.lock().await;
@@ -234,7 +463,83 @@ impl TaskQueue {
 #[derive(Debug, serde::Serialize)]
 pub struct QueueStats {
     pub pending_count: usize,
+    /// Pending tasks still waiting out a retry backoff
+    pub waiting_retry_count: usize,
     pub active_count: usize,
     pub available_slots: usize,
     pub max_concurrent: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> TaskRequest {
+        TaskRequest {
+            prompt: String::new(),
+            workspace_path: None,
+            timeout_secs: None,
+            retry: Default::default(),
+        }
+    }
+
+    fn ago(secs: u64) -> Instant {
+        Instant::now() - Duration::from_secs(secs)
+    }
+
+    fn pop_id(pending: &mut PendingTasks) -> Option<String> {
+        pending.pop().map(|t| t.task_id)
+    }
+
+    #[test]
+    fn higher_priority_first_then_fifo() {
+        let mut pending = PendingTasks::new(Duration::from_secs(60));
+        let now = Instant::now();
+        pending.push("low".into(), request(), 1, now);
+        pending.push("high-1".into(), request(), 9, now);
+        pending.push("high-2".into(), request(), 9, now);
+
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("high-1"));
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("high-2"));
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("low"));
+        assert_eq!(pop_id(&mut pending), None);
+    }
+
+    #[test]
+    fn waiting_raises_effective_priority() {
+        let mut pending = PendingTasks::new(Duration::from_secs(60));
+        // Priority 1 after five intervals counts as 6 and beats a fresh 5
+        pending.push("old".into(), request(), 1, ago(5 * 60 + 1));
+        pending.push("new".into(), request(), 5, Instant::now());
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("old"));
+
+        // Aging off: the plain priority decides
+        let mut pending = PendingTasks::new(Duration::ZERO);
+        pending.push("old".into(), request(), 1, ago(3600));
+        pending.push("new".into(), request(), 5, Instant::now());
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn reprioritize_keeps_age() {
+        let mut pending = PendingTasks::new(Duration::from_secs(60));
+        pending.push("a".into(), request(), 5, ago(120));
+        pending.push("b".into(), request(), 7, Instant::now());
+        assert!(pending.set_priority("a", 6));
+        assert!(!pending.set_priority("missing", 1));
+        // 6 + 2 intervals beats 7
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn retries_wait_for_their_backoff() {
+        let mut pending = PendingTasks::new(Duration::from_secs(60));
+        pending.push("retry".into(), request(), 9, Instant::now() + Duration::from_secs(30));
+        assert_eq!(pending.waiting_for_retry(), 1);
+        assert_eq!(pop_id(&mut pending), None);
+
+        pending.push("ready".into(), request(), 1, Instant::now());
+        assert_eq!(pop_id(&mut pending).as_deref(), Some("ready"));
+        assert!(pending.contains("retry"));
+    }
+}
@@ -1,9 +1,12 @@
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
 use std::thread;
 use tauri::{AppHandle, Emitter};
-use crate::{db, parser};
+use crate::db::EventSource;
+use crate::workflow::{Workflow, WORKFLOW_FILE};
+use crate::{db, epic_tables, parser, search};
 
 pub fn start_watcher(app: AppHandle, project_path: String) -> Result<(), String> {
     let path = Path::new(&project_path);
@@ -17,17 +20,30 @@ pub fn start_watcher(app: AppHandle, project_path: String) -> Result<(), String>
         let mut watcher: RecommendedWatcher =
             Watcher::new(tx, Config::default()).expect("Failed to create watcher");
 
-        let folders = ["backlog", "in-progress", "inprogress", "done", "epics"];
-        for folder in folders {
-            let folder_path = Path::new(&project_path).join(folder);
-            if folder_path.exists() {
-                let _ = watcher.watch(&folder_path, RecursiveMode::NonRecursive);
-            }
+        // The project folder itself is watched for workflow.toml and for
+        // column folders appearing, so the folder watches can follow them
+        let root = PathBuf::from(&project_path);
+        let _ = watcher.watch(&root, RecursiveMode::NonRecursive);
+        let mut watched = HashSet::new();
+        watch_folders(&mut watcher, &project_path, &mut watched);
+
+        // Resources are only watched to keep the search index fresh
+        let resources_path = Path::new(&project_path).join("resources");
+        if resources_path.exists() {
+            let _ = watcher.watch(&resources_path, RecursiveMode::Recursive);
         }
 
         loop {
             match rx.recv() {
+                Ok(Ok(event)) if event.paths.iter().all(|p| p.parent() == Some(root.as_path())) => {
+                    watch_folders(&mut watcher, &project_path, &mut watched);
+                    if event.paths.iter().any(|p| p.ends_with(WORKFLOW_FILE)) {
+                        let _ = app.emit("file-change", event.paths);
+                    }
+                }
                 Ok(Ok(event)) => {
+                    handle_resource_change(&event, &project_path);
+
                     if should_emit_event(&event) {
                         // Handle incremental sync
                         handle_md_file_change(&event, &project_path, &app);
@@ -46,6 +62,26 @@ pub fn start_watcher(app: AppHandle, project_path: String) -> Result<(), String>
     Ok(())
 }
 
+/// Watch the folders the current workflow reads tickets, epics and sprints
+/// from, dropping watches on folders it no longer uses.
+fn watch_folders(watcher: &mut RecommendedWatcher, project_path: &str, watched: &mut HashSet<PathBuf>) {
+    let workflow = Workflow::load_or_default(Path::new(project_path));
+    let wanted: HashSet<PathBuf> = workflow
+        .ticket_folders()
+        .chain(["epics", "sprints"])
+        .map(|folder| Path::new(project_path).join(folder))
+        .filter(|path| path.exists())
+        .collect();
+
+    for stale in watched.difference(&wanted) {
+        let _ = watcher.unwatch(stale);
+    }
+    for path in wanted.difference(watched) {
+        let _ = watcher.watch(path, RecursiveMode::NonRecursive);
+    }
+    *watched = wanted;
+}
+
 fn should_emit_event(event: &Event) -> bool {
     use notify::EventKind::*;
     matches!(
@@ -54,35 +90,74 @@ fn should_emit_event(event: &Event) -> bool {
     ) && event
         .paths
         .iter()
-        .any(|p| p.extension().map_or(false, |ext| ext == "md"))
+        .any(|p| p.extension().is_some_and(|ext| ext == "md"))
+}
+
+fn handle_resource_change(event: &Event, project_path: &str) {
+    use notify::EventKind::*;
+
+    if !matches!(event.kind, Create(_) | Modify(_) | Remove(_)) {
+        return;
+    }
+
+    for path in &event.paths {
+        let in_resources = path.to_str().is_some_and(|s| s.contains("/resources/"));
+        if in_resources && search::is_text_resource(path) {
+            if let Err(e) = db::reindex_resource(project_path, path) {
+                log::warn!("Failed to index {}: {}", path.display(), e);
+            }
+        }
+    }
 }
 
 fn handle_md_file_change(event: &Event, project_path: &str, app: &AppHandle) {
     use notify::EventKind::*;
 
+    // Re-read on every event so edits to workflow.toml apply without a restart
+    let workflow = Workflow::load_or_default(Path::new(project_path));
+
     for path in &event.paths {
-        if !path.extension().map_or(false, |ext| ext == "md") {
+        if path.extension().is_none_or(|ext| ext != "md") {
             continue;
         }
 
         let file_path_str = path.to_string_lossy().to_string();
 
-        // Check if file is in epics folder or ticket folders
-        let is_epic = path.to_str().map_or(false, |s| s.contains("/epics/"));
-        let is_ticket = path.to_str().map_or(false, |s| {
-            s.contains("/backlog/") || s.contains("/inprogress/") || s.contains("/done/")
-        });
+        // Resources are handled by handle_resource_change
+        if path.to_str().is_some_and(|s| s.contains("/resources/")) {
+            continue;
+        }
+
+        // Check if file is in the epics or sprints folder or a ticket folder
+        let is_epic = path.to_str().is_some_and(|s| s.contains("/epics/"));
+        let is_sprint = path.to_str().is_some_and(|s| s.contains("/sprints/"));
+        let column_status = workflow.status_for_path(path);
+        let is_ticket = column_status.is_some();
 
         match event.kind {
             Remove(_) => {
                 // Handle file deletion
                 if is_epic {
                     if let Some(filename) = path.file_stem().and_then(|s| s.to_str()) {
-                        let _ = db::delete_epic(filename);
+                        let _ = db::delete_epic(project_path, &parser::epic_id_from_file_name(filename));
+                    }
+                } else if is_sprint {
+                    if let Some(filename) = path.file_stem().and_then(|s| s.to_str()) {
+                        let _ = db::delete_sprint(project_path, &parser::sprint_id_from_file_name(filename));
                     }
                 } else if is_ticket {
                     if let Some(filename) = path.file_stem().and_then(|s| s.to_str()) {
-                        let _ = db::delete_ticket(filename);
+                        // A move shows up as a remove of the old path; the
+                        // ticket is only gone if no column holds it anymore
+                        let moved = path.parent().and_then(|p| p.parent()).is_some_and(|m2k| {
+                            workflow
+                                .ticket_folders()
+                                .any(|folder| m2k.join(folder).join(format!("{}.md", filename)).exists())
+                        });
+                        if !moved {
+                            let _ = db::delete_ticket(project_path, filename, EventSource::Watcher);
+                            sync_epic_tables(project_path, filename, None);
+                        }
                     }
                 }
                 let _ = app.emit("md-synced", serde_json::json!({
@@ -94,41 +169,37 @@ fn handle_md_file_change(event: &Event, project_path: &str, app: &AppHandle) {
                 // Handle file create/modify
                 if is_epic {
                     if let Some(epic) = parser::parse_epic_file(path) {
-                        let result = db::upsert_epic(&epic, &file_path_str);
+                        let result = db::upsert_epic(project_path, &epic, &file_path_str);
                         let status = if result.is_ok() { "synced" } else { "error" };
                         let _ = app.emit("md-synced", serde_json::json!({
                             "file_path": file_path_str,
                             "status": status
                         }));
                     }
-                } else if is_ticket {
-                    // Determine status from folder
-                    let folder = if path.to_str().map_or(false, |s| s.contains("/backlog/")) {
-                        "backlog"
-                    } else if path.to_str().map_or(false, |s| s.contains("/inprogress/")) {
-                        "inprogress"
-                    } else {
-                        "done"
-                    };
-
-                    // Map folder to status
-                    let new_status = match folder {
-                        "backlog" => "backlog",
-                        "inprogress" => "in_progress",
-                        _ => "done"
-                    };
-
-                    if let Some(ticket) = parser::parse_ticket_file(path, folder) {
+                } else if is_sprint {
+                    if let Some(sprint) = parser::parse_sprint_file(path) {
+                        let result = db::upsert_sprint(project_path, &sprint);
+                        let status = if result.is_ok() { "synced" } else { "error" };
+                        let _ = app.emit("md-synced", serde_json::json!({
+                            "file_path": file_path_str,
+                            "status": status
+                        }));
+                    }
+                } else if let Some(new_status) = column_status {
+                    // Status comes from the workflow column owning the folder
+                    if let Some(ticket) = parser::parse_ticket_file(path, new_status) {
+                        sync_epic_tables(project_path, &ticket.id, Some(&ticket));
+
                         // For move events (detected as Create after Remove), update status
                         // For modify events, upsert full ticket
                         if matches!(event.kind, Create(_)) {
                             // Try status update first (more efficient for moves)
                             let ticket_id = ticket.id.clone();
-                            let status_result = db::update_ticket_status(&ticket_id, new_status, project_path);
+                            let status_result = db::update_ticket_status(project_path, &ticket_id, new_status, EventSource::Watcher);
 
                             // If ticket doesn't exist, do full upsert
                             let result = if status_result.is_err() {
-                                db::upsert_ticket(&ticket)
+                                db::upsert_ticket(project_path, &ticket, EventSource::Watcher)
                             } else {
                                 status_result
                             };
@@ -140,7 +211,7 @@ fn handle_md_file_change(event: &Event, project_path: &str, app: &AppHandle) {
                             }));
                         } else {
                             // Modify event - do full upsert
-                            let result = db::upsert_ticket(&ticket);
+                            let result = db::upsert_ticket(project_path, &ticket, EventSource::ExternalEdit);
                             let status = if result.is_ok() { "synced" } else { "error" };
                             let _ = app.emit("md-synced", serde_json::json!({
                                 "file_path": file_path_str,
@@ -154,3 +225,11 @@ fn handle_md_file_change(event: &Event, project_path: &str, app: &AppHandle) {
         }
     }
 }
+
+/// Keep epic ticket tables in line with a ticket changed on disk. Unchanged
+/// tables are not written, so this does not feed back into the watcher.
+fn sync_epic_tables(project_path: &str, ticket_id: &str, ticket: Option<&parser::Ticket>) {
+    if let Err(e) = epic_tables::sync_ticket_row(&crate::m2k_dir(project_path), ticket_id, ticket) {
+        log::warn!("Failed to update epic tables for {}: {}", ticket_id, e);
+    }
+}
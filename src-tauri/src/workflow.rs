@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// File inside `.m2k` that defines the board columns of a project.
+pub const WORKFLOW_FILE: &str = "workflow.toml";
+
+/// Folders under `.m2k` that can never hold tickets.
+const RESERVED_FOLDERS: [&str; 3] = ["epics", "resources", "sprints"];
+
+pub const DEFAULT_WORKFLOW_TOML: &str = r#"# Board columns, in order. Each column stores its tickets in `.m2k/<folder>/`.
+# `category` is one of "todo", "active" or "done" and drives readiness,
+# dependency checks and completion stats. When omitted, the first column is
+# "todo", the last is "done" and everything in between is "active".
+#
+# Optional per column:
+#   transitions = ["in_progress"]  statuses a ticket may move to from here
+#                                  (any status when omitted)
+#   wip_limit = 3                  maximum number of tickets in the column
+
+[[columns]]
+status = "backlog"
+name = "Backlog"
+folder = "backlog"
+
+[[columns]]
+status = "in_progress"
+name = "In Progress"
+folder = "inprogress"
+aliases = ["in-progress"]
+
+[[columns]]
+status = "done"
+name = "Done"
+folder = "done"
+"#;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnCategory {
+    Todo,
+    Active,
+    Done,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowColumn {
+    /// Status value stored on tickets, e.g. `in_progress`
+    pub status: String,
+    /// Display name for the board
+    #[serde(default)]
+    pub name: String,
+    pub folder: String,
+    /// Extra folder names that map to this column when reading
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub category: Option<ColumnCategory>,
+    /// Statuses a ticket may move to from this column; `None` allows all
+    #[serde(default)]
+    pub transitions: Option<Vec<String>>,
+    #[serde(default, alias = "wip_limit")]
+    pub wip_limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Workflow {
+    pub columns: Vec<WorkflowColumn>,
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Self::parse(DEFAULT_WORKFLOW_TOML).expect("default workflow is valid")
+    }
+}
+
+impl Workflow {
+    /// Read `.m2k/workflow.toml`, falling back to the default columns when the
+    /// file does not exist.
+    pub fn load(m2k_path: &Path) -> Result<Self, String> {
+        let path = m2k_path.join(WORKFLOW_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", WORKFLOW_FILE, e))?;
+        Self::parse(&content)
+    }
+
+    /// Like `load`, but an invalid file is logged and replaced by the default
+    /// so the board keeps working while the user fixes it.
+    pub fn load_or_default(m2k_path: &Path) -> Self {
+        Self::load(m2k_path).unwrap_or_else(|e| {
+            log::warn!("Using default workflow: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut workflow: Workflow = toml::from_str(content)
+            .map_err(|e| format!("Failed to parse {}: {}", WORKFLOW_FILE, e))?;
+
+        let count = workflow.columns.len();
+        for (i, column) in workflow.columns.iter_mut().enumerate() {
+            if column.name.is_empty() {
+                column.name = column.status.clone();
+            }
+            if column.category.is_none() {
+                column.category = Some(if i == 0 {
+                    ColumnCategory::Todo
+                } else if i + 1 == count {
+                    ColumnCategory::Done
+                } else {
+                    ColumnCategory::Active
+                });
+            }
+        }
+
+        workflow.validate()?;
+        Ok(workflow)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.columns.is_empty() {
+            return Err("Workflow must define at least one column".to_string());
+        }
+
+        let mut statuses = HashSet::new();
+        let mut folders = HashSet::new();
+        for column in &self.columns {
+            if column.status.trim().is_empty() {
+                return Err("Workflow column is missing a status".to_string());
+            }
+            if !statuses.insert(column.status.as_str()) {
+                return Err(format!("Duplicate workflow status: {}", column.status));
+            }
+
+            for folder in column.folders() {
+                let valid = !folder.is_empty()
+                    && !folder.starts_with('.')
+                    && !folder.contains(['/', '\\'])
+                    && !RESERVED_FOLDERS.contains(&folder);
+                if !valid {
+                    return Err(format!("Invalid folder for status {}: {}", column.status, folder));
+                }
+                if !folders.insert(folder) {
+                    return Err(format!("Folder {} is used by more than one column", folder));
+                }
+            }
+        }
+
+        for column in &self.columns {
+            for target in column.transitions.iter().flatten() {
+                if !statuses.contains(target.as_str()) {
+                    return Err(format!(
+                        "Transition from {} targets unknown status: {}",
+                        column.status, target
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn column(&self, status: &str) -> Option<&WorkflowColumn> {
+        self.columns.iter().find(|c| c.status == status)
+    }
+
+    /// Column whose folder (or alias) is `folder`.
+    pub fn column_for_folder(&self, folder: &str) -> Option<&WorkflowColumn> {
+        self.columns.iter().find(|c| c.folders().any(|f| f == folder))
+    }
+
+    pub fn statuses(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(|c| c.status.as_str())
+    }
+
+    /// Every folder that may contain tickets, aliases included.
+    pub fn ticket_folders(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().flat_map(|c| c.folders())
+    }
+
+    /// Status of the column a ticket file lives in, based on its parent folder.
+    pub fn status_for_path(&self, path: &Path) -> Option<&str> {
+        let folder = path.parent()?.file_name()?.to_str()?;
+        self.column_for_folder(folder).map(|c| c.status.as_str())
+    }
+
+    /// Status named by a label as people write it in epic tables:
+    /// `in_progress`, `In Progress` or the folder name `inprogress`.
+    pub fn status_for_label(&self, label: &str) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|c| {
+                c.status.eq_ignore_ascii_case(label)
+                    || c.name.eq_ignore_ascii_case(label)
+                    || c.folders().any(|f| f.eq_ignore_ascii_case(label))
+            })
+            .map(|c| c.status.as_str())
+    }
+
+    /// Column that newly created tickets start in.
+    pub fn initial_column(&self) -> &WorkflowColumn {
+        &self.columns[0]
+    }
+
+    pub fn category(&self, status: &str) -> Option<ColumnCategory> {
+        self.column(status).and_then(|c| c.category)
+    }
+
+    pub fn is_done(&self, status: &str) -> bool {
+        self.category(status) == Some(ColumnCategory::Done)
+    }
+
+    /// Whether a ticket may move from `from` to `to`. Staying in the same
+    /// column is always allowed.
+    pub fn can_transition(&self, from: &str, to: &str) -> bool {
+        from == to
+            || self
+                .column(from)
+                .and_then(|c| c.transitions.as_ref())
+                .is_none_or(|allowed| allowed.iter().any(|s| s == to))
+    }
+
+    /// Statuses reachable from `from` in one move.
+    pub fn allowed_targets(&self, from: &str) -> Vec<String> {
+        self.statuses()
+            .filter(|to| *to != from && self.can_transition(from, to))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl WorkflowColumn {
+    pub fn folders(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.folder.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
@@ -0,0 +1,876 @@
+use crate::frontmatter::{self, FrontmatterFormat};
+use crate::ids;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A ticket or epic markdown file loaded for editing.
+///
+/// Every edit splices the original source in place, so bytes outside the
+/// field, section or table row being changed are written back untouched.
+#[derive(Debug, Clone)]
+pub struct MarkdownDocument {
+    path: Option<PathBuf>,
+    original: String,
+    content: String,
+}
+
+/// A heading and the byte range of the body that belongs to it.
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub title: String,
+    /// Range from the end of the heading line up to the next heading of the
+    /// same or a higher level
+    pub body: Range<usize>,
+}
+
+/// A GFM table with the byte ranges of its rows and cells.
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub header: TableRow,
+    /// Range of the `|---|---|` line
+    pub delimiter: Range<usize>,
+    pub rows: Vec<TableRow>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TableRow {
+    /// Range of the row line, without its line break
+    pub line: Range<usize>,
+    /// Ranges of the raw text between pipes
+    pub cells: Vec<Range<usize>>,
+}
+
+impl MarkdownDocument {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            original: content.clone(),
+            content,
+        })
+    }
+
+    pub fn from_string(content: String) -> Self {
+        Self {
+            path: None,
+            original: content.clone(),
+            content,
+        }
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.content != self.original
+    }
+
+    /// Write the document back to the file it was loaded from. Unchanged
+    /// documents are not written, so file watchers do not see no-op edits.
+    pub fn save(&mut self) -> Result<bool, String> {
+        let path = self.path.clone().ok_or("Document has no file path")?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&mut self, path: &Path) -> Result<bool, String> {
+        if !self.is_dirty() && self.path.as_deref() == Some(path) {
+            return Ok(false);
+        }
+
+        fs::write(path, &self.content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        self.original = self.content.clone();
+        self.path = Some(path.to_path_buf());
+        Ok(true)
+    }
+
+    /// Replace the whole document, e.g. with the text of an editor buffer.
+    /// Line breaks follow the ones already in the file.
+    pub fn replace_content(&mut self, content: &str) {
+        let nl = self.line_ending();
+        self.content = normalize_newlines(content, nl);
+    }
+
+    fn splice(&mut self, range: Range<usize>, replacement: &str) {
+        self.content.replace_range(range, replacement);
+    }
+
+    fn line_ending(&self) -> &'static str {
+        if self.content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    /// Offset of the first byte after the frontmatter block, if any.
+    fn body_start(&self) -> usize {
+        frontmatter::locate(&self.content).map_or(0, |block| block.body_start)
+    }
+
+    // ----- Frontmatter -----
+
+    /// Set or remove (`None`) a key in the frontmatter block, creating a YAML
+    /// block when the file has none.
+    pub fn set_frontmatter_field(&mut self, key: &str, value: Option<&Value>) -> Result<(), String> {
+        let Some(block) = frontmatter::locate(&self.content) else {
+            if let Some(value) = value {
+                let nl = self.line_ending();
+                let line = format_yaml_entry(key, value)?;
+                self.splice(0..0, &format!("---{nl}{line}{nl}---{nl}"));
+            }
+            return Ok(());
+        };
+
+        let nl = self.line_ending();
+        let entry = match value {
+            Some(value) => Some(match block.format {
+                FrontmatterFormat::Yaml => format_yaml_entry(key, value)?,
+                FrontmatterFormat::Toml => format_toml_entry(key, value)?,
+            }),
+            None => None,
+        };
+
+        match find_frontmatter_entry(&self.content, block.inner.clone(), key, block.format) {
+            Some(range) => {
+                let replacement = entry.map(|e| format!("{}{}", e, nl)).unwrap_or_default();
+                self.splice(range, &replacement);
+            }
+            None => {
+                // The inner range always ends at the start of the closing fence line
+                if let Some(entry) = entry {
+                    let insert_at = block.inner.end;
+                    self.splice(insert_at..insert_at, &format!("{}{}", entry, nl));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // ----- Title and bold fields -----
+
+    /// Range of the text after `# ` on the title heading line.
+    fn title_text_range(&self) -> Option<Range<usize>> {
+        let start = self.body_start();
+        let mut offset = start;
+        for line in self.content[start..].split_inclusive('\n') {
+            let text = line.trim_end_matches(['\r', '\n']);
+            if text.starts_with('#') && !text.starts_with("##") {
+                let hashes = text.len() - text.trim_start_matches('#').len();
+                let rest = &text[hashes..];
+                if rest.starts_with(char::is_whitespace) {
+                    let lead = rest.len() - rest.trim_start().len();
+                    return Some(offset + hashes + lead..offset + text.len());
+                }
+            }
+            offset += line.len();
+        }
+        None
+    }
+
+    /// Text of the title heading, including any `T-XXX:` prefix.
+    pub fn title_heading(&self) -> Option<&str> {
+        self.title_text_range().map(|range| &self.content[range])
+    }
+
+    /// 1-based line number of a byte offset.
+    pub fn line_number(&self, offset: usize) -> usize {
+        self.content[..offset.min(self.content.len())].matches('\n').count() + 1
+    }
+
+    /// Line of the title heading, or 1 when there is none.
+    pub fn title_line(&self) -> usize {
+        self.title_text_range().map_or(1, |range| self.line_number(range.start))
+    }
+
+    /// Replace the title while keeping any `T-XXX:` prefix.
+    pub fn set_title(&mut self, title: &str) -> Result<(), String> {
+        let range = self.title_text_range().ok_or("Document has no title heading")?;
+        let current = &self.content[range.clone()];
+
+        let replacement = match id_prefix(current) {
+            Some(idx) => format!("{} {}", &current[..=idx], title.trim()),
+            None => title.trim().to_string(),
+        };
+
+        self.splice(range, &replacement);
+        Ok(())
+    }
+
+    /// Rewrite the heading prefix, e.g. `T-047` in `# T-047: Title`.
+    pub fn set_title_prefix(&mut self, prefix: &str) -> Result<(), String> {
+        let range = self.title_text_range().ok_or("Document has no title heading")?;
+        let current = &self.content[range.clone()];
+
+        let replacement = match id_prefix(current) {
+            Some(idx) => format!("{}{}", prefix, &current[idx..]),
+            None if ids::is_ticket_id(current.trim()) => prefix.to_string(),
+            None => format!("{}: {}", prefix, current),
+        };
+
+        self.splice(range, &replacement);
+        Ok(())
+    }
+
+    /// Range of the value on a `**Label:** value` line.
+    fn bold_field_range(&self, label: &str) -> Option<Range<usize>> {
+        let re = Regex::new(&format!(r"(?m)^\*\*{}:\*\*[ \t]*([^\r\n]*)", regex::escape(label))).ok()?;
+        let start = self.body_start();
+        re.captures(&self.content[start..])
+            .and_then(|caps| caps.get(1))
+            .map(|m| start + m.start()..start + m.end())
+    }
+
+    pub fn bold_field(&self, label: &str) -> Option<&str> {
+        self.bold_field_range(label)
+            .map(|range| self.content[range].trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn bold_field_line(&self, label: &str) -> Option<usize> {
+        self.bold_field_range(label).map(|range| self.line_number(range.start))
+    }
+
+    /// Set a `**Label:** value` line, adding it under the title when missing.
+    pub fn set_bold_field(&mut self, label: &str, value: &str) -> Result<(), String> {
+        if let Some(range) = self.bold_field_range(label) {
+            let line_start = self.content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+            let has_gap = self.content[line_start..range.start].ends_with([' ', '\t']);
+            let replacement = if has_gap || value.is_empty() {
+                value.to_string()
+            } else {
+                format!(" {}", value)
+            };
+            self.splice(range, &replacement);
+            return Ok(());
+        }
+
+        let nl = self.line_ending();
+        let insert_at = match self.title_text_range() {
+            Some(range) => line_end(&self.content, range.end),
+            None => self.body_start(),
+        };
+        let prefix = if insert_at > 0 && !self.content[..insert_at].ends_with('\n') {
+            nl
+        } else {
+            ""
+        };
+        self.splice(
+            insert_at..insert_at,
+            &format!("{prefix}{nl}**{label}:** {value}{nl}"),
+        );
+        Ok(())
+    }
+
+    // ----- Sections -----
+
+    pub fn sections(&self) -> Vec<Section> {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+            | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+
+        let mut headings: Vec<(String, usize, Range<usize>)> = Vec::new();
+        let mut current: Option<(String, usize, Range<usize>)> = None;
+
+        for (event, range) in Parser::new_ext(&self.content, options).into_offset_iter() {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    let start = self.content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+                    let end = line_end(&self.content, range.end.saturating_sub(1).max(range.start));
+                    current = Some((String::new(), level as usize, start..end));
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((title, _, _)) = current.as_mut() {
+                        title.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    if let Some((title, level, range)) = current.take() {
+                        headings.push((title.trim().to_string(), level, range));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        headings
+            .iter()
+            .enumerate()
+            .map(|(i, (title, level, heading))| {
+                let end = headings[i + 1..]
+                    .iter()
+                    .find(|(_, next_level, _)| next_level <= level)
+                    .map_or(self.content.len(), |(_, _, next)| next.start);
+                Section {
+                    title: title.clone(),
+                    body: heading.end..end.max(heading.end),
+                }
+            })
+            .collect()
+    }
+
+    pub fn find_section(&self, name: &str) -> Option<Section> {
+        self.sections()
+            .into_iter()
+            .find(|s| s.title.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn section(&self, name: &str) -> Option<&str> {
+        self.find_section(name)
+            .map(|s| self.content[s.body].trim())
+    }
+
+    /// Replace the body of a `## name` section, appending the section at the
+    /// end of the document when it does not exist yet.
+    pub fn set_section(&mut self, name: &str, body: &str) -> Result<(), String> {
+        let nl = self.line_ending();
+        let body = normalize_newlines(body.trim(), nl);
+
+        if let Some(section) = self.find_section(name) {
+            let current = &self.content[section.body.clone()];
+            let at_eof = section.body.end == self.content.len();
+
+            let replacement = if current.trim().is_empty() {
+                match (body.is_empty(), at_eof) {
+                    (true, _) => return Ok(()),
+                    (false, true) => format!("{}{}", body, nl),
+                    (false, false) => format!("{}{}{}", body, nl, nl),
+                }
+            } else {
+                // Keep the blank lines around the body exactly as they were
+                let leading_ws = &current[..current.len() - current.trim_start().len()];
+                let lead = &leading_ws[..leading_ws.rfind('\n').map_or(0, |i| i + 1)];
+                let trail = &current[current.trim_end().len()..];
+                format!("{}{}{}", lead, body, trail)
+            };
+
+            self.splice(section.body, &replacement);
+            return Ok(());
+        }
+
+        let mut prefix = String::new();
+        if !self.content.is_empty() {
+            if !self.content.ends_with('\n') {
+                prefix.push_str(nl);
+            }
+            if !self.content.ends_with(&format!("{nl}{nl}")) {
+                prefix.push_str(nl);
+            }
+        }
+        let end = self.content.len();
+        self.splice(end..end, &format!("{prefix}## {name}{nl}{body}{nl}"));
+        Ok(())
+    }
+
+    /// Set (`Some`) or flip (`None`) the checkbox of the `index`-th `- ` item in
+    /// a section, turning a plain item into a checkbox if needed. Returns the
+    /// new state.
+    pub fn set_checklist_item(&mut self, section: &str, index: usize, checked: Option<bool>) -> Result<bool, String> {
+        let found = self
+            .find_section(section)
+            .ok_or(format!("Section '{}' not found", section))?;
+
+        let mut offset = found.body.start;
+        let mut seen = 0;
+        let mut target = None;
+        for line in self.content[found.body.clone()].split_inclusive('\n') {
+            // Same boundary the parser uses for list sections
+            if line.starts_with("##") {
+                break;
+            }
+            let trimmed = line.trim_start();
+            if let Some(after_dash) = trimmed.strip_prefix('-') {
+                if seen == index {
+                    let gap = after_dash.len() - after_dash.trim_start().len();
+                    target = Some(offset + (line.len() - trimmed.len()) + 1 + gap);
+                    break;
+                }
+                seen += 1;
+            }
+            offset += line.len();
+        }
+
+        let item_start = target.ok_or(format!("Item {} not found in '{}'", index, section))?;
+        let current = match self.content.get(item_start..item_start + 3) {
+            Some("[ ]") => Some(false),
+            Some("[x]") | Some("[X]") => Some(true),
+            _ => None,
+        };
+
+        let state = checked.unwrap_or(!current.unwrap_or(false));
+        let mark = if state { "[x]" } else { "[ ]" };
+        match current {
+            Some(_) => self.splice(item_start..item_start + 3, mark),
+            None => self.splice(item_start..item_start, &format!("{} ", mark)),
+        }
+
+        Ok(state)
+    }
+
+    // ----- Tables -----
+
+    pub fn tables(&self) -> Vec<Table> {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+            | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+
+        let mut tables = Vec::new();
+        for (event, range) in Parser::new_ext(&self.content, options).into_offset_iter() {
+            if let Event::Start(Tag::Table(_)) = event {
+                if let Some(table) = self.split_table(range) {
+                    tables.push(table);
+                }
+            }
+        }
+        tables
+    }
+
+    fn split_table(&self, range: Range<usize>) -> Option<Table> {
+        let mut rows = Vec::new();
+        let mut offset = range.start;
+        for line in self.content[range.clone()].split_inclusive('\n') {
+            let text_len = line.trim_end_matches(['\r', '\n']).len();
+            if text_len > 0 {
+                rows.push(split_row(&self.content, offset..offset + text_len));
+            }
+            offset += line.len();
+        }
+
+        // Header, delimiter row, then body rows
+        if rows.len() < 2 {
+            return None;
+        }
+        let header = rows.remove(0);
+        let delimiter = rows.remove(0).line;
+
+        Some(Table {
+            header,
+            delimiter,
+            rows,
+        })
+    }
+
+    pub fn cell_text(&self, range: &Range<usize>) -> &str {
+        self.content[range.clone()].trim()
+    }
+
+    /// Column index of a header cell, matched case-insensitively.
+    pub fn column_index(&self, table: &Table, column: &str) -> Option<usize> {
+        table
+            .header
+            .cells
+            .iter()
+            .position(|cell| self.cell_text(cell).eq_ignore_ascii_case(column))
+    }
+
+    /// Find the table and row whose first cell equals `key`.
+    pub fn find_row(&self, key: &str) -> Option<(Table, usize)> {
+        self.tables().into_iter().find_map(|table| {
+            let index = table.rows.iter().position(|row| {
+                row.cells
+                    .first()
+                    .is_some_and(|cell| self.cell_text(cell) == key)
+            })?;
+            Some((table, index))
+        })
+    }
+
+    /// Replace one cell, reusing the cell's padding so aligned tables stay aligned.
+    pub fn set_cell(&mut self, cell: Range<usize>, value: &str) {
+        let raw = &self.content[cell.clone()];
+        let lead_len = raw.len() - raw.trim_start().len();
+        let trail_len = raw.len() - raw.trim_end().len();
+
+        // Only pad to the old width when the column was visibly aligned
+        let value = value.replace('|', "\\|");
+        let width = raw.len() - lead_len;
+        let pad = if trail_len > 1 && value.len() < width {
+            width - value.len()
+        } else {
+            trail_len.min(1)
+        };
+        let replacement = format!("{}{}{}", &raw[..lead_len], value, " ".repeat(pad));
+
+        if replacement != raw {
+            self.splice(cell, &replacement);
+        }
+    }
+
+    /// Append a row after the last row of `table`.
+    pub fn append_row(&mut self, table: &Table, cells: &[&str]) {
+        let nl = self.line_ending();
+        let after = table.rows.last().map_or(&table.delimiter, |row| &row.line);
+        let insert_at = line_end(&self.content, after.end);
+
+        let row = format!(
+            "| {} |",
+            cells
+                .iter()
+                .map(|c| c.replace('|', "\\|"))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+
+        let insertion = if self.content[..insert_at].ends_with('\n') {
+            format!("{}{}", row, nl)
+        } else {
+            format!("{}{}", nl, row)
+        };
+        self.splice(insert_at..insert_at, &insertion);
+    }
+
+    /// Remove a row line, including its line break.
+    pub fn remove_row(&mut self, row: &TableRow) {
+        let start = row.line.start;
+        let end = line_end(&self.content, row.line.end);
+        self.splice(start..end, "");
+    }
+
+    // ----- Epic ticket tables -----
+
+    /// The `| ID | Title | Status |` table listing an epic's tickets.
+    pub fn ticket_table(&self) -> Option<Table> {
+        self.tables().into_iter().find(|table| {
+            table
+                .header
+                .cells
+                .first()
+                .is_some_and(|cell| self.cell_text(cell).eq_ignore_ascii_case("ID"))
+        })
+    }
+
+    /// Update the status cell of a ticket row. Returns false when the epic
+    /// does not list the ticket.
+    pub fn set_ticket_row_status(&mut self, ticket_id: &str, status: &str) -> bool {
+        self.set_ticket_row_cell(ticket_id, "Status", 2, status)
+    }
+
+    pub fn set_ticket_row_title(&mut self, ticket_id: &str, title: &str) -> bool {
+        self.set_ticket_row_cell(ticket_id, "Title", 1, title)
+    }
+
+    pub fn set_ticket_row_id(&mut self, ticket_id: &str, new_id: &str) -> bool {
+        self.set_ticket_row_cell(ticket_id, "ID", 0, new_id)
+    }
+
+    fn set_ticket_row_cell(&mut self, ticket_id: &str, column: &str, fallback: usize, value: &str) -> bool {
+        let Some((table, index)) = self.find_row(ticket_id) else {
+            return false;
+        };
+        let column = self.column_index(&table, column).unwrap_or(fallback);
+        match table.rows[index].cells.get(column) {
+            Some(cell) => {
+                self.set_cell(cell.clone(), value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Documents saved as one change. When a save fails, or the caller rolls
+/// back after a later step fails, every document already written gets its
+/// previous content back.
+pub struct DocumentBatch {
+    written: Vec<(PathBuf, String)>,
+}
+
+impl DocumentBatch {
+    pub fn save<'a>(docs: impl IntoIterator<Item = &'a mut MarkdownDocument>) -> Result<Self, String> {
+        let mut batch = Self { written: Vec::new() };
+        for doc in docs {
+            let (path, original) = (doc.path.clone(), doc.original.clone());
+            match doc.save() {
+                Ok(true) => batch.written.extend(path.map(|path| (path, original))),
+                Ok(false) => {}
+                Err(e) => {
+                    batch.rollback();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(batch)
+    }
+
+    pub fn rollback(self) {
+        for (path, original) in self.written.iter().rev() {
+            if let Err(e) = fs::write(path, original) {
+                log::error!("Failed to restore {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Offset of the colon after a leading ticket ID, so `Fix: crash on save`
+/// is not mistaken for a prefixed title.
+fn id_prefix(title: &str) -> Option<usize> {
+    title
+        .find(':')
+        .filter(|&idx| ids::is_ticket_id(title[..idx].trim()))
+}
+
+/// Offset just past the line break of the line containing `pos`.
+fn line_end(content: &str, pos: usize) -> usize {
+    match content[pos..].find('\n') {
+        Some(i) => pos + i + 1,
+        None => content.len(),
+    }
+}
+
+fn normalize_newlines(text: &str, nl: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', nl)
+}
+
+/// Split a table row into the raw cell ranges between pipes.
+fn split_row(content: &str, line: Range<usize>) -> TableRow {
+    let text = &content[line.clone()];
+    let bytes = text.as_bytes();
+
+    let mut pipes = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'|' => {
+                pipes.push(i);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+
+    let leading = text.trim_start().starts_with('|');
+    let trailing = text.trim_end().ends_with('|') && !text.trim_end().ends_with("\\|");
+
+    let mut bounds = Vec::new();
+    if !leading {
+        bounds.push(0);
+    }
+    for pipe in &pipes {
+        bounds.push(*pipe);
+    }
+    if !trailing {
+        bounds.push(text.len());
+    }
+
+    let cells = bounds
+        .windows(2)
+        .map(|w| {
+            let start = if w[0] == 0 && !leading { 0 } else { w[0] + 1 };
+            line.start + start..line.start + w[1]
+        })
+        .collect();
+
+    TableRow { line, cells }
+}
+
+fn find_frontmatter_entry(
+    content: &str,
+    inner: Range<usize>,
+    key: &str,
+    format: FrontmatterFormat,
+) -> Option<Range<usize>> {
+    let separator = match format {
+        FrontmatterFormat::Yaml => ':',
+        FrontmatterFormat::Toml => '=',
+    };
+
+    let mut offset = inner.start;
+    let mut found: Option<usize> = None;
+    for line in content[inner.clone()].split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        if let Some(start) = found {
+            // YAML block values continue on indented or list lines
+            let continues = format == FrontmatterFormat::Yaml
+                && (text.starts_with(char::is_whitespace) || text.starts_with("- "));
+            if !continues {
+                return Some(start..offset);
+            }
+        } else {
+            let trimmed = text.trim_start_matches(['"', '\'']);
+            if text == text.trim_start()
+                && trimmed.starts_with(key)
+                && trimmed[key.len()..]
+                    .trim_start_matches(['"', '\''])
+                    .trim_start()
+                    .starts_with(separator)
+            {
+                found = Some(offset);
+            }
+        }
+        offset += line.len();
+    }
+
+    found.map(|start| start..inner.end)
+}
+
+fn format_yaml_entry(key: &str, value: &Value) -> Result<String, String> {
+    let rendered = match value {
+        Value::String(s) => yaml_scalar(s),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => yaml_scalar(s),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Null => String::new(),
+        other => serde_json::to_string(other).map_err(|e| e.to_string())?,
+    };
+    Ok(format!("{}: {}", key, rendered).trim_end().to_string())
+}
+
+/// Write `s` bare only when it reads back as the same string. Anything a
+/// YAML parser could take for a number, bool, null or date is quoted, along
+/// with the YAML 1.1 words (`yes`, `off`, ...) that older parsers treat as bools.
+fn yaml_scalar(s: &str) -> String {
+    let reparses = matches!(
+        serde_yaml::from_str::<serde_yaml::Value>(s),
+        Ok(serde_yaml::Value::String(ref parsed)) if parsed == s
+    );
+    let plain = reparses
+        && s.chars().all(|c| c.is_alphanumeric() || " _-./@+".contains(c))
+        && !s.starts_with(|c: char| c.is_ascii_digit() || "-+.@".contains(c))
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "y" | "n" | "yes" | "no" | "on" | "off" | "true" | "false" | "null" | "~"
+        );
+    if plain {
+        s.to_string()
+    } else {
+        serde_json::to_string(s).unwrap_or_else(|_| format!("\"{}\"", s))
+    }
+}
+
+fn format_toml_entry(key: &str, value: &Value) -> Result<String, String> {
+    let value = toml::Value::try_from(value)
+        .map_err(|e| format!("Value cannot be written as TOML: {}", e))?;
+    Ok(format!("{} = {}", key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_file(content: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!("m2k-writer-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.md", COUNTER.fetch_add(1, Ordering::SeqCst)));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// Load `before`, apply `edit`, save, and compare the file on disk with `after`.
+    fn round_trip(before: &str, edit: impl FnOnce(&mut MarkdownDocument), after: &str) {
+        let path = temp_file(before);
+        let mut doc = MarkdownDocument::load(&path).unwrap();
+        edit(&mut doc);
+        doc.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), after);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unchanged_document_is_not_written() {
+        let path = temp_file("# T-001: Title\n");
+        let mut doc = MarkdownDocument::load(&path).unwrap();
+        assert!(!doc.save().unwrap());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn frontmatter_field_keeps_other_keys() {
+        round_trip(
+            "---\ntitle: Keep   spacing\npriority: high\ntags: [a, b]\n---\n\n# T-001: Title\n",
+            |doc| doc.set_frontmatter_field("priority", Some(&Value::from("low"))).unwrap(),
+            "---\ntitle: Keep   spacing\npriority: low\ntags: [a, b]\n---\n\n# T-001: Title\n",
+        );
+    }
+
+    #[test]
+    fn frontmatter_field_keeps_crlf() {
+        round_trip(
+            "---\r\nepic: EPIC-001\r\n---\r\n# T-001: Title\r\n",
+            |doc| doc.set_frontmatter_field("owner", Some(&Value::from("sam"))).unwrap(),
+            "---\r\nepic: EPIC-001\r\nowner: sam\r\n---\r\n# T-001: Title\r\n",
+        );
+    }
+
+    #[test]
+    fn yaml_scalars_that_look_like_other_types_are_quoted() {
+        for value in ["5", "1.0", "yes", "Off", "true", "~", "-x", "2024-01-01"] {
+            assert_eq!(yaml_scalar(value), format!("\"{}\"", value));
+        }
+        assert_eq!(yaml_scalar("EPIC-001"), "EPIC-001");
+        assert_eq!(yaml_scalar("plain words"), "plain words");
+    }
+
+    #[test]
+    fn title_keeps_id_prefix_only() {
+        round_trip(
+            "# T-001: Old\n\nBody\n",
+            |doc| doc.set_title("New").unwrap(),
+            "# T-001: New\n\nBody\n",
+        );
+        round_trip(
+            "# Fix: crash on save\n",
+            |doc| doc.set_title("Fix: crash on load").unwrap(),
+            "# Fix: crash on load\n",
+        );
+        round_trip(
+            "# Fix: crash on save\n",
+            |doc| doc.set_title_prefix("T-002").unwrap(),
+            "# T-002: Fix: crash on save\n",
+        );
+    }
+
+    #[test]
+    fn section_edit_leaves_other_sections_alone() {
+        round_trip(
+            "# T-001: Title\r\n\r\n## Description\r\nOld text\r\n\r\n## Notes\r\n  keep  this \r\n",
+            |doc| doc.set_section("Description", "New\ntext").unwrap(),
+            "# T-001: Title\r\n\r\n## Description\r\nNew\r\ntext\r\n\r\n## Notes\r\n  keep  this \r\n",
+        );
+    }
+
+    #[test]
+    fn table_cell_edit_keeps_spacing_and_escaped_pipes() {
+        let before = "| ID    | Title        | Status |\n|-------|--------------|--------|\n\
+                      | T-001 | a \\| b       | todo   |\n| T-002 | Second       | todo   |\n";
+        let after = "| ID    | Title        | Status |\n|-------|--------------|--------|\n\
+                     | T-001 | a \\| b       | todo   |\n| T-002 | x \\| y       | todo   |\n";
+        round_trip(
+            before,
+            |doc| {
+                let table = doc.tables().remove(0);
+                assert_eq!(doc.cell_text(&table.rows[0].cells[1]), "a \\| b");
+                doc.set_cell(table.rows[1].cells[1].clone(), "x | y");
+            },
+            after,
+        );
+    }
+
+    #[test]
+    fn replace_content_follows_file_line_endings() {
+        round_trip(
+            "# T-001: Title\r\nOld\r\n",
+            |doc| doc.replace_content("# T-001: Title\nNew\n"),
+            "# T-001: Title\r\nNew\r\n",
+        );
+    }
+}
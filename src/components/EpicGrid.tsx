@@ -1,5 +1,6 @@
 import { useState, useMemo } from "react";
 import { useAppStore } from "../lib/store";
+import { isDoneStatus } from "../lib/workflow";
 import { BookOpen, CheckCircle2, Circle } from "lucide-react";
 import type { EpicPriority } from "../types";
 
@@ -27,6 +28,7 @@ function PriorityBadge({ priority }: { priority: EpicPriority }) {
 export function EpicGrid() {
   const epics = useAppStore((s) => s.epics);
   const tickets = useAppStore((s) => s.tickets);
+  const workflow = useAppStore((s) => s.workflow);
   const setSelectedEpic = useAppStore((s) => s.setSelectedEpic);
 
   const [sortBy, setSortBy] = useState<SortBy>("priority");
@@ -55,7 +57,7 @@ export function EpicGrid() {
   const getEpicStats = (epicId: string) => {
     const epicTickets = tickets.filter((t) => t.epic === epicId);
     const total = epicTickets.length;
-    const completed = epicTickets.filter((t) => t.status === "done").length;
+    const completed = epicTickets.filter((t) => isDoneStatus(workflow, t.status)).length;
     return { total, completed };
   };
 
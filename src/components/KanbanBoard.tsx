@@ -1,10 +1,11 @@
-import { TrendingUp, Layers, Wifi, WifiOff, RefreshCw, AlertCircle, ListTodo, PlayCircle, CheckSquare } from "lucide-react";
+import { TrendingUp, Layers, Wifi, WifiOff, RefreshCw, AlertCircle } from "lucide-react";
 import { useAppStore } from "../lib/store";
 import { KanbanColumn } from "./KanbanColumn";
 import { StatsPill } from "./StatsPill";
+import { StatusPills } from "./StatusPills";
 import { EpicFilter } from "./EpicFilter";
 import { EpicExecuteButton } from "./EpicExecuteButton";
-import { useStats, formatNumber } from "../hooks/useStats";
+import { useStats } from "../hooks/useStats";
 import { useApiStatus } from "../hooks/useApiStatus";
 import type { Ticket } from "../types";
 
@@ -51,9 +52,7 @@ function StatsSection() {
         <>
           <StatsPill icon={Layers} label="Epics" value={`${stats.completedEpics}/${stats.totalEpics}`} color="blue" size="xs" />
           <StatsPill icon={TrendingUp} label="Progress" value={`${stats.epicCompletionPercent}%`} color={stats.epicCompletionPercent >= 75 ? "green" : stats.epicCompletionPercent >= 50 ? "blue" : "purple"} size="xs" />
-          <StatsPill icon={ListTodo} label="Backlog" value={formatNumber(stats.backlogTickets)} color="default" size="xs" />
-          <StatsPill icon={PlayCircle} label="Active" value={formatNumber(stats.inProgressTickets)} color="orange" size="xs" />
-          <StatsPill icon={CheckSquare} label="Done" value={formatNumber(stats.doneTickets)} color="green" size="xs" />
+          <StatusPills statusCounts={stats.statusCounts} size="xs" />
         </>
       )}
       <button
@@ -68,19 +67,43 @@ function StatsSection() {
   );
 }
 
+function IdCollisionBanner() {
+  const collisions = useAppStore((s) => s.idCollisions);
+  if (collisions.length === 0) return null;
+
+  return (
+    <div
+      role="alert"
+      className="mx-3 md:mx-4 mt-3 px-3 py-2 rounded-lg border border-[var(--geist-warning)] text-xs text-[var(--geist-foreground)] flex items-start gap-2"
+    >
+      <AlertCircle size={14} className="mt-0.5 shrink-0 text-[var(--geist-warning)]" aria-hidden="true" />
+      <div className="min-w-0">
+        <p className="font-medium">
+          {collisions.length === 1 ? "A ticket ID is" : `${collisions.length} ticket IDs are`} used by several files. Only the first file of each is shown; renumber the others.
+        </p>
+        <ul className="mt-1 space-y-0.5">
+          {collisions.map((c) => (
+            <li key={c.id} className="truncate" title={c.files.join("\n")}>
+              <span className="font-mono">{c.id}</span>: {c.files.map((f) => f.split("/").slice(-2).join("/")).join(", ")}
+            </li>
+          ))}
+        </ul>
+      </div>
+    </div>
+  );
+}
+
 export function KanbanBoard() {
   const tickets = useAppStore((s) => s.tickets);
   const selectedEpic = useAppStore((s) => s.selectedEpic);
   const setViewMode = useAppStore((s) => s.setViewMode);
   const resetPrdState = useAppStore((s) => s.resetPrdState);
+  const workflow = useAppStore((s) => s.workflow);
 
   const filteredTickets = selectedEpic
     ? tickets.filter((t) => t.epic === selectedEpic)
     : tickets;
 
-  const backlog = sortByTicketId(filteredTickets.filter((t) => t.status === "backlog"));
-  const inProgress = sortByTicketId(filteredTickets.filter((t) => t.status === "in_progress"));
-  const done = sortByTicketId(filteredTickets.filter((t) => t.status === "done"));
 
   const handleAddTicketToBacklog = () => {
     resetPrdState();
@@ -90,10 +113,17 @@ export function KanbanBoard() {
   return (
     <div className="flex flex-col h-full min-h-0">
       <StatsSection />
+      <IdCollisionBanner />
       <div className="flex justify-center gap-3 md:gap-4 p-3 md:p-4 flex-1 min-h-0 overflow-auto">
-        <KanbanColumn title="Backlog" tickets={backlog} onAddTicket={selectedEpic ? handleAddTicketToBacklog : undefined} />
-        <KanbanColumn title="In Progress" tickets={inProgress} />
-        <KanbanColumn title="Done" tickets={done} />
+        {workflow.columns.map((column, i) => (
+          <KanbanColumn
+            key={column.status}
+            title={column.name}
+            category={column.category}
+            tickets={sortByTicketId(filteredTickets.filter((t) => t.status === column.status))}
+            onAddTicket={i === 0 && selectedEpic ? handleAddTicketToBacklog : undefined}
+          />
+        ))}
       </div>
     </div>
   );
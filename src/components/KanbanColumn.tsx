@@ -1,20 +1,21 @@
 import { Inbox, Loader2, CheckCircle2, Plus } from "lucide-react";
-import type { Ticket } from "../types";
+import type { Ticket, ColumnCategory } from "../types";
 import { TicketCard } from "./TicketCard";
 
-const columnIcons: Record<string, React.ReactNode> = {
-  Backlog: <Inbox size={16} aria-hidden="true" />,
-  "In Progress": <Loader2 size={16} className="text-[var(--geist-success)]" aria-hidden="true" />,
-  Done: <CheckCircle2 size={16} className="text-emerald-500" aria-hidden="true" />,
+const columnIcons: Record<ColumnCategory, React.ReactNode> = {
+  todo: <Inbox size={16} aria-hidden="true" />,
+  active: <Loader2 size={16} className="text-[var(--geist-success)]" aria-hidden="true" />,
+  done: <CheckCircle2 size={16} className="text-emerald-500" aria-hidden="true" />,
 };
 
 interface Props {
   title: string;
+  category: ColumnCategory | null;
   tickets: Ticket[];
   onAddTicket?: () => void;
 }
 
-export function KanbanColumn({ title, tickets, onAddTicket }: Props) {
+export function KanbanColumn({ title, category, tickets, onAddTicket }: Props) {
   return (
     <section
       className="flex flex-col bg-[var(--geist-accents-1)] rounded-lg overflow-hidden border border-[var(--geist-accents-2)] max-h-[300px] md:max-h-none md:h-full w-[300px] shrink-0"
@@ -22,7 +23,7 @@ export function KanbanColumn({ title, tickets, onAddTicket }: Props) {
     >
       <header className="px-3 md:px-4 py-2 md:py-3 border-b border-[var(--geist-accents-2)] flex items-center justify-between shrink-0">
         <div className="flex items-center gap-2">
-          {columnIcons[title]}
+          {category && columnIcons[category]}
           <h2 className="font-medium text-sm md:text-base">{title}</h2>
         </div>
         <div className="flex items-center gap-2">
@@ -8,6 +8,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { FileText, Image, FolderOpen, File, Save, Columns3 } from "lucide-react";
 import { Select } from "./Select";
 import { Toggle } from "./Toggle";
+import type { ReservedId } from "../types";
 
 interface FileNode {
   name: string;
@@ -286,6 +287,17 @@ export function PRDMode() {
     setError(null);
   };
 
+  // Write a new document over its reserved empty file, handing the ID back
+  // if the write fails
+  const saveReserved = async (filePath: string, content: string) => {
+    try {
+      await invoke("save_markdown_file", { path: filePath, content });
+    } catch (e) {
+      await invoke("release_reserved_id", { filePath }).catch(() => {});
+      throw e;
+    }
+  };
+
   const handleSave = async () => {
     if (!projectPath) return;
 
@@ -340,21 +352,20 @@ export function PRDMode() {
 
         showToast("File saved successfully", "success");
       } else if (prdState.docType === "epic") {
-        const nextId = await invoke<number>("get_next_epic_id", {
-          projectPath: `${projectPath}`,
-        });
-        const paddedId = nextId.toString().padStart(3, "0");
         const titleMatch = prdState.content.match(/^# EPIC-[\d{}\w]+: (.+)$/m);
         const title = titleMatch?.[1]?.trim() || "Untitled";
-        const safeName = title
-          .replace(/[^a-zA-Z0-9\s-]/g, "")
-          .replace(/\s+/g, "-");
-        const filePath = `${projectPath}/epics/EPIC-${paddedId}-${safeName}.md`;
+        // The backend reserves the ID with an empty file named after the title
+        const reserved = await invoke<ReservedId>("get_next_epic_id", {
+          projectPath: `${projectPath}`,
+          title,
+        });
+        const paddedId = reserved.id.replace(/^EPIC-/, "");
+        const filePath = reserved.filePath;
         const content = prdState.content.replace(
           /EPIC-\{ID\}/g,
           `EPIC-${paddedId}`
         );
-        await invoke("save_markdown_file", { path: filePath, content });
+        await saveReserved(filePath, content);
 
         // Optimistic update - add epic to store immediately
         const scopeMatch = content.match(/## Scope\s+(.*?)(?=\n##|\n\n##|$)/s);
@@ -385,11 +396,12 @@ export function PRDMode() {
           setSaving(false);
           return;
         }
-        const nextId = await invoke<number>("get_next_ticket_id", {
+        // The backend reserves the ID with an empty file in the first column
+        const reserved = await invoke<ReservedId>("get_next_ticket_id", {
           projectPath: `${projectPath}`,
         });
-        const paddedId = nextId.toString().padStart(3, "0");
-        const filePath = `${projectPath}/backlog/T-${paddedId}.md`;
+        const paddedId = reserved.id.replace(/^T-/, "");
+        const filePath = reserved.filePath;
         let content = prdState.content
           .replace(/T-\{ID\}/g, `T-${paddedId}`)
           .replace(/EPIC-\{EPIC_ID\}/g, selectedEpic)
@@ -400,7 +412,7 @@ export function PRDMode() {
             `**Epic:** ${selectedEpic}`
           );
         }
-        await invoke("save_markdown_file", { path: filePath, content });
+        await saveReserved(filePath, content);
 
         // Optimistic update - add ticket to store immediately
         const titleMatch = content.match(/^# T-\d+: (.+)$/m);
@@ -409,7 +421,11 @@ export function PRDMode() {
         const description = descMatch?.[1]?.trim() || "";
         const criteriaMatch = content.match(/## Acceptance Criteria\s+(.*?)(?=\n##|$)/s);
         const criteriaText = criteriaMatch?.[1]?.trim() || "";
-        const criteria = criteriaText.split('\n').filter(line => line.trim().startsWith('-')).map(line => line.trim().substring(1).trim());
+        const criteria = criteriaText.split('\n').filter(line => line.trim().startsWith('-')).map(line => {
+          const item = line.trim().substring(1).trim();
+          const box = item.match(/^\[([ xX])\]\s*(.*)$/);
+          return box ? { text: box[2], checked: box[1] !== " " } : { text: item, checked: null };
+        });
         const newTicket = {
           id: `T-${paddedId}`,
           title: title,
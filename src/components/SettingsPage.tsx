@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { useAppStore } from "../lib/store";
 import { Toast, useToast } from "./Toast";
 import { Toggle } from "./Toggle";
+import { loadConfig, saveConfig } from "../lib/config";
 import packageJson from "../../package.json";
 
 interface ProjectSettings {
@@ -33,6 +34,7 @@ export function SettingsPage() {
   const [backupPath, setBackupPath] = useState("");
   const [hasBackupPath, setHasBackupPath] = useState(false);
   const [syncing, setSyncing] = useState(false);
+  const [requeueInterrupted, setRequeueInterrupted] = useState(false);
   const { toast, showToast, hideToast } = useToast();
 
   useEffect(() => {
@@ -41,6 +43,9 @@ export function SettingsPage() {
         const exists = await invoke<boolean>("has_api_key");
         setHasExistingKey(exists);
 
+        const config = await loadConfig();
+        setRequeueInterrupted(config.interrupted_task_policy === "requeue");
+
         // Load project settings if project is loaded
         if (projectPath) {
           const settings = await invoke<ProjectSettings | null>("get_project_settings", {
@@ -70,6 +75,16 @@ export function SettingsPage() {
     init();
   }, [projectPath]);
 
+  const handleRequeueInterruptedChange = async (enabled: boolean) => {
+    try {
+      const config = await loadConfig();
+      await saveConfig({ ...config, interrupted_task_policy: enabled ? "requeue" : "fail" });
+      setRequeueInterrupted(enabled);
+    } catch (err) {
+      showToast(`Failed to save setting: ${err}`, "error");
+    }
+  };
+
   const handleCancel = () => {
     setViewMode("kanban");
   };
@@ -337,6 +352,31 @@ export function SettingsPage() {
             </div>
           </div>
 
+          {/* Claude Task Settings */}
+          <div className="bg-[var(--geist-accents-1)] border border-[var(--geist-accents-2)] rounded-lg p-4">
+            <h2 className="text-base font-medium text-[var(--geist-foreground)] mb-4">
+              Claude Tasks
+            </h2>
+
+            <div className="flex items-center justify-between">
+              <div>
+                <label htmlFor="requeue-toggle" className="text-sm font-medium text-[var(--geist-foreground)] block mb-1">
+                  Resume Interrupted Tasks
+                </label>
+                <p className="text-xs text-[var(--geist-accents-5)]">
+                  Run tasks cut off by quitting the app again on the next start, up to their attempt limit. When off, they are marked failed.
+                </p>
+              </div>
+              <Toggle
+                id="requeue-toggle"
+                checked={requeueInterrupted}
+                onChange={handleRequeueInterruptedChange}
+                label="Resume Interrupted Tasks"
+                showLabel={false}
+              />
+            </div>
+          </div>
+
           {/* .m2k Backup */}
           {projectPath && (
             <div className="bg-[var(--geist-accents-1)] border border-[var(--geist-accents-2)] rounded-lg p-4">
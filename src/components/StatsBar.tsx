@@ -1,6 +1,7 @@
-import { TrendingUp, Layers, Wifi, WifiOff, RefreshCw, AlertCircle, ListTodo, PlayCircle, CheckSquare } from "lucide-react";
+import { TrendingUp, Layers, Wifi, WifiOff, RefreshCw, AlertCircle, CheckSquare } from "lucide-react";
 import { StatsPill } from "./StatsPill";
-import { useStats, formatNumber } from "../hooks/useStats";
+import { StatusPills } from "./StatusPills";
+import { useStats } from "../hooks/useStats";
 import { useApiStatus } from "../hooks/useApiStatus";
 
 interface StatsBarProps {
@@ -34,9 +35,7 @@ export function StatsBar({ vertical = false }: StatsBarProps) {
           <>
             <StatsPill icon={Layers} label="Epics" value={`${stats.completedEpics}/${stats.totalEpics}`} color="blue" size="sm" />
             <StatsPill icon={TrendingUp} label="Progress" value={`${stats.epicCompletionPercent}%`} color={stats.epicCompletionPercent >= 75 ? "green" : stats.epicCompletionPercent >= 50 ? "blue" : "purple"} size="sm" />
-            <StatsPill icon={ListTodo} label="Backlog" value={formatNumber(stats.backlogTickets)} color="default" size="sm" />
-            <StatsPill icon={PlayCircle} label="Active" value={formatNumber(stats.inProgressTickets)} color="orange" size="sm" />
-            <StatsPill icon={CheckSquare} label="Done" value={formatNumber(stats.doneTickets)} color="green" size="sm" />
+            <StatusPills statusCounts={stats.statusCounts} />
           </>
         )}
         <button
@@ -75,27 +74,7 @@ export function StatsBar({ vertical = false }: StatsBarProps) {
 
           {/* Ticket Stats */}
           <div className="hidden sm:flex items-center gap-2 border-l border-[var(--geist-accents-3)] pl-3">
-            <StatsPill
-              icon={ListTodo}
-              label="Backlog"
-              value={formatNumber(stats.backlogTickets)}
-              color="default"
-              size="sm"
-            />
-            <StatsPill
-              icon={PlayCircle}
-              label="In Progress"
-              value={formatNumber(stats.inProgressTickets)}
-              color="orange"
-              size="sm"
-            />
-            <StatsPill
-              icon={CheckSquare}
-              label="Done"
-              value={formatNumber(stats.doneTickets)}
-              color="green"
-              size="sm"
-            />
+            <StatusPills statusCounts={stats.statusCounts} />
           </div>
 
           {/* Mobile ticket summary */}
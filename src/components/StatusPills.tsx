@@ -0,0 +1,38 @@
+import { ListTodo, PlayCircle, CheckSquare } from "lucide-react";
+import { StatsPill } from "./StatsPill";
+import { useAppStore } from "../lib/store";
+import { formatNumber } from "../hooks/useStats";
+
+const categoryStyles = {
+  todo: { icon: ListTodo, color: "default" },
+  active: { icon: PlayCircle, color: "orange" },
+  done: { icon: CheckSquare, color: "green" },
+} as const;
+
+interface StatusPillsProps {
+  statusCounts: Record<string, number>;
+  size?: "xs" | "sm";
+}
+
+/** One pill per workflow column, in board order */
+export function StatusPills({ statusCounts, size = "sm" }: StatusPillsProps) {
+  const workflow = useAppStore((s) => s.workflow);
+
+  return (
+    <>
+      {workflow.columns.map((column) => {
+        const style = categoryStyles[column.category ?? "active"];
+        return (
+          <StatsPill
+            key={column.status}
+            icon={style.icon}
+            label={column.name}
+            value={formatNumber(statusCounts[column.status] ?? 0)}
+            color={style.color}
+            size={size}
+          />
+        );
+      })}
+    </>
+  );
+}
@@ -5,7 +5,8 @@ import { open, confirm } from "@tauri-apps/plugin-dialog";
 import { useAppStore, RegisteredProject } from "../lib/store";
 import { loadConfig, saveConfig } from "../lib/config";
 import { debounce } from "../lib/debounce";
-import type { Ticket, Epic } from "../types";
+import type { Ticket, Epic, SyncReport, Workflow } from "../types";
+import { DEFAULT_WORKFLOW } from "../lib/workflow";
 
 export function useProjectLoader() {
   const setProjectPath = useAppStore((s) => s.setProjectPath);
@@ -17,6 +18,8 @@ export function useProjectLoader() {
   const setSidebarCollapsed = useAppStore((s) => s.setSidebarCollapsed);
   const sidebarCollapsed = useAppStore((s) => s.sidebarCollapsed);
   const setProjectLoading = useAppStore((s) => s.setProjectLoading);
+  const setIdCollisions = useAppStore((s) => s.setIdCollisions);
+  const setWorkflow = useAppStore((s) => s.setWorkflow);
   const prevSidebarCollapsed = useRef<boolean | null>(null);
 
   const validateProjectPath = useCallback(async (path: string): Promise<boolean> => {
@@ -52,25 +55,31 @@ export function useProjectLoader() {
       // Sync MD files to database
       try {
         console.log("Syncing project data to database...");
-        await invoke("sync_md_to_db", { projectPath: m2kPath });
+        const report = await invoke<SyncReport>("sync_md_to_db", { projectPath: m2kPath });
+        setIdCollisions(report.collisions);
         console.log("Database sync complete");
       } catch (e) {
         console.warn("Failed to sync MD to database:", e);
       }
 
-      const [tickets, epics] = await Promise.all([
+      const [tickets, epics, workflow] = await Promise.all([
         invoke<Ticket[]>("parse_tickets", { path: m2kPath }),
         invoke<Epic[]>("parse_epics", { path: m2kPath }),
+        invoke<Workflow>("get_workflow", { projectPath: m2kPath }).catch((e) => {
+          console.warn("Invalid workflow.toml, using default columns:", e);
+          return DEFAULT_WORKFLOW;
+        }),
       ]);
       setTickets(tickets);
       setEpics(epics);
+      setWorkflow(workflow);
       await invoke("start_watcher", { path: m2kPath });
       return { success: true };
     } catch (e) {
       console.error("Failed to load project:", e);
       return { success: false, error: String(e) };
     }
-  }, [setTickets, setEpics, validateProjectPath]);
+  }, [setTickets, setEpics, setIdCollisions, setWorkflow, validateProjectPath]);
 
   const loadRegisteredProjects = useCallback(async () => {
     try {
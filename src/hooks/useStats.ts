@@ -14,6 +14,8 @@ export interface AppStats {
   backlogTickets: number;
   inProgressTickets: number;
   doneTickets: number;
+  /** Ticket count per workflow status, including custom columns */
+  statusCounts: Record<string, number>;
   ticketCompletionPercent: number;
 }
 
@@ -24,6 +26,7 @@ interface DBStats {
   backlog_tickets: number;
   inprogress_tickets: number;
   done_tickets: number;
+  status_counts: Record<string, number>;
 }
 
 export function useStats(): AppStats {
@@ -39,6 +42,7 @@ export function useStats(): AppStats {
     backlogTickets: 0,
     inProgressTickets: 0,
     doneTickets: 0,
+    statusCounts: {},
     ticketCompletionPercent: 0,
   });
 
@@ -55,6 +59,7 @@ export function useStats(): AppStats {
         backlogTickets: 0,
         inProgressTickets: 0,
         doneTickets: 0,
+        statusCounts: {},
         ticketCompletionPercent: 0,
       });
       return;
@@ -93,6 +98,7 @@ export function useStats(): AppStats {
           backlogTickets: settings.backlog_tickets,
           inProgressTickets: settings.inprogress_tickets,
           doneTickets: settings.done_tickets,
+          statusCounts: settings.status_counts,
           ticketCompletionPercent,
         });
       } catch (e) {
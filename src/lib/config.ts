@@ -5,6 +5,8 @@ export interface AppConfig {
   theme: string;
   sidebar_collapsed: boolean;
   default_editor_mode: string;
+  /** What to do at startup with Claude tasks the last quit cut off */
+  interrupted_task_policy: "requeue" | "fail";
 }
 
 export async function loadConfig(): Promise<AppConfig> {
@@ -18,7 +18,7 @@ export function buildEpicPrompt({ epic, tickets, projectPath }: EpicPromptOption
   const ticketDetails = backlogTickets
     .map((ticket) => {
       const criteriaList = ticket.criteria.length > 0
-        ? ticket.criteria.map((c) => `  - ${c}`).join("\n")
+        ? ticket.criteria.map((c) => `  - ${c.checked ? "[x] " : ""}${c.text}`).join("\n")
         : "  - No criteria specified";
 
       return `### ${ticket.id}: ${ticket.title}
@@ -68,7 +68,7 @@ Start: ${backlogTickets[0]?.id || "No tickets"}
  */
 export function buildTicketPrompt(ticket: Ticket, projectPath: string): string {
   const criteriaList = ticket.criteria.length > 0
-    ? ticket.criteria.map((c) => `- ${c}`).join("\n")
+    ? ticket.criteria.map((c) => `- ${c.checked ? "[x] " : ""}${c.text}`).join("\n")
     : "- No criteria specified";
 
   return `# ${ticket.id}: ${ticket.title}
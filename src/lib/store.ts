@@ -1,5 +1,6 @@
 import { create } from "zustand";
-import type { Ticket, Epic } from "../types";
+import type { Ticket, Epic, IdCollision, Workflow } from "../types";
+import { DEFAULT_WORKFLOW } from "./workflow";
 
 export interface RegisteredProject {
   id: number;
@@ -33,6 +34,10 @@ interface AppState {
   sidebarCollapsed: boolean;
   saveCallback: (() => void) | null;
   projectLoading: boolean;
+  /** Ticket IDs claimed by several files, from the last sync */
+  idCollisions: IdCollision[];
+  /** Board columns of the active project */
+  workflow: Workflow;
   setTickets: (tickets: Ticket[]) => void;
   setEpics: (epics: Epic[]) => void;
   setSelectedEpic: (epicId: string | null) => void;
@@ -48,6 +53,8 @@ interface AppState {
   setSaveCallback: (callback: (() => void) | null) => void;
   triggerSave: () => void;
   setProjectLoading: (loading: boolean) => void;
+  setIdCollisions: (collisions: IdCollision[]) => void;
+  setWorkflow: (workflow: Workflow) => void;
 }
 
 const defaultPrdState: PrdState = {
@@ -71,6 +78,8 @@ export const useAppStore = create<AppState>((set, get) => ({
   sidebarCollapsed: false,
   saveCallback: null,
   projectLoading: false,
+  idCollisions: [],
+  workflow: DEFAULT_WORKFLOW,
   setTickets: (tickets) => set({ tickets }),
   setEpics: (epics) => set({ epics }),
   setSelectedEpic: (epicId) => set({ selectedEpic: epicId }),
@@ -95,4 +104,6 @@ export const useAppStore = create<AppState>((set, get) => ({
     }
   },
   setProjectLoading: (loading) => set({ projectLoading: loading }),
+  setIdCollisions: (collisions) => set({ idCollisions: collisions }),
+  setWorkflow: (workflow) => set({ workflow }),
 }));
@@ -1,35 +1,41 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { TicketStatus, Ticket } from "../types";
+import type { TicketStatus, Ticket, MoveOutcome, EventSource, EpicMoveOutcome, Workflow, ColumnCategory } from "../types";
+import { DEFAULT_WORKFLOW, categoryOf } from "./workflow";
 
 /**
- * Move a ticket to a new status folder
+ * Move a ticket to a new status folder. Rejects with a `MoveError` when a
+ * workflow rule refuses the move; pass `force` to override it.
+ * `eventSource` is what the ticket history records as the mover.
  */
 export async function moveTicketToStatus(
   projectPath: string,
   ticketId: string,
-  newStatus: TicketStatus
-): Promise<void> {
-  await invoke("move_ticket_to_status", {
+  newStatus: TicketStatus,
+  force = false,
+  eventSource: EventSource = "ui"
+): Promise<MoveOutcome> {
+  return invoke<MoveOutcome>("move_ticket_to_status", {
     projectPath,
     ticketId,
     newStatus,
+    force,
+    eventSource,
   });
 }
 
 /**
- * Update ticket status in the epic file
+ * Move a ticket to another epic. The ticket file and both epic tables are
+ * rewritten together; nothing changes if any write fails.
  */
-export async function updateEpicTicketStatus(
+export async function moveTicketToEpic(
   projectPath: string,
-  epicId: string,
   ticketId: string,
-  newStatus: string
-): Promise<void> {
-  await invoke("update_epic_ticket_status", {
+  newEpicId: string
+): Promise<EpicMoveOutcome> {
+  return invoke<EpicMoveOutcome>("move_ticket_to_epic", {
     projectPath,
-    epicId,
     ticketId,
-    newStatus,
+    newEpicId,
   });
 }
 
@@ -40,8 +46,8 @@ export async function startTicket(
   projectPath: string,
   ticket: Ticket
 ): Promise<void> {
+  // The backend keeps the epic's ticket table in sync
   await moveTicketToStatus(projectPath, ticket.id, "in_progress");
-  await updateEpicTicketStatus(projectPath, ticket.epic, ticket.id, "in_progress");
 }
 
 /**
@@ -52,7 +58,6 @@ export async function completeTicket(
   ticket: Ticket
 ): Promise<void> {
   await moveTicketToStatus(projectPath, ticket.id, "done");
-  await updateEpicTicketStatus(projectPath, ticket.epic, ticket.id, "done");
 }
 
 /**
@@ -66,17 +71,25 @@ export function getNextBacklogTicket(tickets: Ticket[], epicId: string): Ticket
 }
 
 /**
- * Check if all tickets in an epic are done
+ * Check if all tickets in an epic are in a done column of the workflow
  */
-export function isEpicComplete(tickets: Ticket[], epicId: string): boolean {
+export function isEpicComplete(
+  tickets: Ticket[],
+  epicId: string,
+  workflow: Workflow = DEFAULT_WORKFLOW
+): boolean {
   const epicTickets = tickets.filter((t) => t.epic === epicId);
-  return epicTickets.length > 0 && epicTickets.every((t) => t.status === "done");
+  return epicTickets.length > 0 && epicTickets.every((t) => categoryOf(workflow, t.status) === "done");
 }
 
 /**
- * Get progress stats for an epic
+ * Get progress stats for an epic, counting tickets by workflow category
  */
-export function getEpicProgress(tickets: Ticket[], epicId: string): {
+export function getEpicProgress(
+  tickets: Ticket[],
+  epicId: string,
+  workflow: Workflow = DEFAULT_WORKFLOW
+): {
   total: number;
   done: number;
   inProgress: number;
@@ -84,9 +97,11 @@ export function getEpicProgress(tickets: Ticket[], epicId: string): {
   percentage: number;
 } {
   const epicTickets = tickets.filter((t) => t.epic === epicId);
-  const done = epicTickets.filter((t) => t.status === "done").length;
-  const inProgress = epicTickets.filter((t) => t.status === "in_progress").length;
-  const backlog = epicTickets.filter((t) => t.status === "backlog").length;
+  const inCategory = (category: ColumnCategory) =>
+    epicTickets.filter((t) => categoryOf(workflow, t.status) === category).length;
+  const done = inCategory("done");
+  const inProgress = inCategory("active");
+  const backlog = inCategory("todo");
   const total = epicTickets.length;
   const percentage = total > 0 ? Math.round((done / total) * 100) : 0;
 
@@ -0,0 +1,21 @@
+import type { ColumnCategory, Workflow } from "../types";
+
+/**
+ * Columns used until a project's workflow has been loaded. Mirrors the
+ * backend default for projects without a `workflow.toml`.
+ */
+export const DEFAULT_WORKFLOW: Workflow = {
+  columns: [
+    { status: "backlog", name: "Backlog", folder: "backlog", aliases: [], category: "todo", transitions: null, wipLimit: null },
+    { status: "in_progress", name: "In Progress", folder: "inprogress", aliases: ["in-progress"], category: "active", transitions: null, wipLimit: null },
+    { status: "done", name: "Done", folder: "done", aliases: [], category: "done", transitions: null, wipLimit: null },
+  ],
+};
+
+export function categoryOf(workflow: Workflow, status: string): ColumnCategory | null {
+  return workflow.columns.find((c) => c.status === status)?.category ?? null;
+}
+
+export function isDoneStatus(workflow: Workflow, status: string): boolean {
+  return categoryOf(workflow, status) === "done";
+}
@@ -1,13 +1,57 @@
 export type TicketStatus = "backlog" | "in_progress" | "done";
 
+export type ColumnCategory = "todo" | "active" | "done";
+
+export interface WorkflowColumn {
+  status: string;
+  name: string;
+  folder: string;
+  aliases: string[];
+  category: ColumnCategory | null;
+  transitions: string[] | null;
+  wipLimit: number | null;
+}
+
+export interface Workflow {
+  columns: WorkflowColumn[];
+}
+
+export interface MoveOutcome {
+  warnings: string[];
+  overrides: string[];
+}
+
+export type MoveError =
+  | { kind: "transitionNotAllowed"; message: string; from: string; to: string; allowed: string[] }
+  | { kind: "wipLimitReached"; message: string; status: string; limit: number; count: number }
+  | { kind: "blocked"; message: string; blockers: string[] }
+  | { kind: "failed"; message: string };
+
+export interface Criterion {
+  text: string;
+  /** null for plain `- item` lines without a checkbox */
+  checked: boolean | null;
+}
+
 export interface Ticket {
   id: string;
   title: string;
   epic: string;
   description: string;
-  criteria: string[];
+  criteria: Criterion[];
+  criteriaProgress?: number | null;
+  technicalNotes?: string;
+  dependencies?: string[];
+  testing?: string;
+  sections?: Record<string, string>;
   status: TicketStatus;
   filePath: string;
+  sprint?: string | null;
+  assignee?: string | null;
+  labels?: string[];
+  estimate?: number | null;
+  dueDate?: string | null;
+  extra?: Record<string, unknown>;
 }
 
 export type EpicPriority = "P1" | "P2" | "P3" | "P4";
@@ -18,4 +62,276 @@ export interface Epic {
   priority: EpicPriority;
   scope: string;
   tickets: string[];
+  assignee?: string | null;
+  labels?: string[];
+  estimate?: number | null;
+  dueDate?: string | null;
+  extra?: Record<string, unknown>;
+}
+
+export type SearchKind = "ticket" | "epic" | "resource";
+
+export interface SearchFilters {
+  kinds?: SearchKind[];
+  status?: string;
+  epic?: string;
+  limit?: number;
+}
+
+export interface SearchHit {
+  kind: SearchKind;
+  id: string;
+  /** HTML with matches wrapped in <mark> */
+  title: string;
+  /** HTML with matches wrapped in <mark> */
+  snippet: string;
+  filePath: string;
+  status: string | null;
+  epicId: string | null;
+  rank: number;
+}
+
+export type EventSource = "ui" | "watcher" | "external_edit" | "ai_task";
+
+export type TicketEventType =
+  | "created"
+  | "status_changed"
+  | "title_changed"
+  | "epic_changed"
+  | "sprint_changed"
+  | "renumbered"
+  | "deleted";
+
+export interface TicketEvent {
+  id: number;
+  ticketId: string;
+  eventType: TicketEventType;
+  fromValue: string | null;
+  toValue: string | null;
+  source: EventSource;
+  createdAt: string;
+}
+
+export interface ActivityPage {
+  /** Newest first */
+  events: TicketEvent[];
+  total: number;
+  limit: number;
+  offset: number;
+}
+
+export interface ChartPoint {
+  /** Date (YYYY-MM-DD) or ticket ID, depending on the series */
+  x: string;
+  y: number;
+  label: string | null;
+}
+
+export interface ChartSeries {
+  name: string;
+  points: ChartPoint[];
+}
+
+export interface TicketTiming {
+  ticketId: string;
+  epicId: string;
+  status: string;
+  createdAt: string | null;
+  startedAt: string | null;
+  completedAt: string | null;
+  leadTimeDays: number | null;
+  cycleTimeDays: number | null;
+}
+
+export interface Percentiles {
+  p50: number;
+  p85: number;
+  p95: number;
+}
+
+export interface EpicFlow {
+  epicId: string;
+  completed: number;
+  leadTime: Percentiles | null;
+  cycleTime: Percentiles | null;
+}
+
+export interface FlowAnalytics {
+  from: string;
+  to: string;
+  leadTime: ChartSeries;
+  cycleTime: ChartSeries;
+  /** Keyed by the Monday starting each week */
+  throughput: ChartSeries;
+  inProgressAge: ChartSeries;
+  averageInProgressAgeDays: number | null;
+  epics: EpicFlow[];
+}
+
+export interface ScopeChange {
+  date: string;
+  ticketId: string;
+  /** 1 when the ticket joined the scope, -1 when it left */
+  delta: number;
+  points: number;
+}
+
+export interface BurnChart {
+  scopeId: string;
+  from: string;
+  to: string;
+  scope: ChartSeries;
+  completed: ChartSeries;
+  remaining: ChartSeries;
+  /** Weighted by estimate; unestimated tickets count as 1 */
+  scopePoints: ChartSeries;
+  completedPoints: ChartSeries;
+  remainingPoints: ChartSeries;
+  scopeChanges: ScopeChange[];
+  /** Tickets completed per day over the last four weeks */
+  recentThroughput: number;
+  projectedCompletion: string | null;
+}
+
+export type SprintState = "planned" | "active" | "closed";
+
+export interface Sprint {
+  id: string;
+  title: string;
+  /** YYYY-MM-DD */
+  startDate: string | null;
+  /** YYYY-MM-DD, inclusive */
+  endDate: string | null;
+  goals: string[];
+  state: SprintState;
+  filePath: string;
+}
+
+export interface SprintProgress {
+  sprintId: string;
+  state: SprintState;
+  totalTickets: number;
+  doneTickets: number;
+  /** Sum of estimates; unestimated tickets count as 1 */
+  totalPoints: number;
+  donePoints: number;
+  statusCounts: Record<string, number>;
+  /** Negative once the end date has passed */
+  daysRemaining: number | null;
+}
+
+export interface CloseSprintOutcome {
+  sprintId: string;
+  completed: string[];
+  rolledOver: string[];
+  nextSprint: string | null;
+}
+
+export interface ProjectStats {
+  totalEpics: number;
+  /** Epics with at least one ticket, all of them done */
+  completedEpics: number;
+  totalTickets: number;
+  statusCounts: Record<string, number>;
+  totalCriteria: number;
+  completedCriteria: number;
+}
+
+export interface SnapshotMismatch {
+  id: string;
+  field: string;
+  fileValue: string | null;
+  snapshotValue: string | null;
+}
+
+export interface CounterDrift {
+  counter: "epic" | "ticket";
+  stored: number;
+  highestOnDisk: number;
+}
+
+export interface StatsDrift {
+  inSync: boolean;
+  /** Computed from the markdown files */
+  files: ProjectStats;
+  /** As reported by get_project_settings */
+  snapshot: ProjectStats;
+  missingFromSnapshot: string[];
+  staleInSnapshot: string[];
+  mismatches: SnapshotMismatch[];
+  counters: CounterDrift[];
+}
+
+export interface ReservedId {
+  number: number;
+  /** e.g. "T-042" or "EPIC-007" */
+  id: string;
+  /** Empty file holding the ID; saving the document overwrites it */
+  filePath: string;
+}
+
+export interface IdCollision {
+  id: string;
+  /** Files claiming the ID; sync keeps the first */
+  files: string[];
+}
+
+export interface SyncReport {
+  collisions: IdCollision[];
+}
+
+export type IdIssueKind = "duplicate" | "heading_mismatch";
+
+export interface IdIssue {
+  kind: IdIssueKind;
+  id: string;
+  files: string[];
+  /** ID in the `# T-xxx:` heading, for heading mismatches */
+  headingId: string | null;
+  message: string;
+}
+
+export interface RenumberOutcome {
+  oldId: string;
+  newId: string;
+  filePath: string;
+  /** Epics and tickets whose references were rewritten */
+  updatedFiles: string[];
+  warnings: string[];
+}
+
+export type LintSeverity = "error" | "warning" | "info";
+
+export type LintFix =
+  | { kind: "set_epic_row_status"; epicFile: string; ticketId: string; status: string }
+  | { kind: "add_epic_row"; epicFile: string; ticketId: string; title: string; status: string }
+  | { kind: "remove_epic_row"; epicFile: string; ticketId: string }
+  | { kind: "add_section"; file: string; section: string; body: string };
+
+export interface LintDiagnostic {
+  file: string;
+  /** 1-based */
+  line: number;
+  severity: LintSeverity;
+  /** Stable rule ID, e.g. "unknown-epic" */
+  rule: string;
+  message: string;
+  /** Pass back to apply_lint_fixes as-is */
+  fix: LintFix | null;
+}
+
+export interface LintReport {
+  diagnostics: LintDiagnostic[];
+  errors: number;
+  warnings: number;
+  fixable: number;
+}
+
+export interface EpicMoveOutcome {
+  ticketId: string;
+  /** Empty when the ticket had no epic */
+  fromEpic: string;
+  toEpic: string;
+  /** The ticket and the epics whose tables changed */
+  updatedFiles: string[];
 }